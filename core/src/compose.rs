@@ -6,9 +6,99 @@ pub struct View(pub Vec<Node>);
 #[derive(Clone, Debug)]
 pub enum Node {
     HeadAppend(View),
+    /// Nodes to add to the end of <body>, after all other content
+    ///
+    /// Eg. deferred `<script>` tags, or containers for modals
+    BodyEndAppend(View),
     Element(Element),
     Fragment(View),
+    /// Text, which is escaped when rendered
     Text(String),
+    /// Trusted markup, which is rendered as-is
+    Raw(String),
+    /// Markup rendered at compile time by `view!`
+    Static(StaticView),
+    /// Attributes of the <html>, <head>, or <body> element of a `Document`
+    ///
+    /// Renders nothing in place. An attribute replaces any earlier attribute with the same name
+    DocumentAttributes(DocumentTag, Vec<Attribute>),
+}
+
+/// Element which is always in a `Document`, and which can be given attributes from anywhere in a
+/// view
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DocumentTag {
+    Html,
+    Head,
+    Body,
+}
+
+impl DocumentTag {
+    /// Tag of element
+    pub fn tag(self) -> Tag {
+        match self {
+            DocumentTag::Html => Tag::Html,
+            DocumentTag::Head => Tag::Head,
+            DocumentTag::Body => Tag::Body,
+        }
+    }
+}
+
+/// A value which renders to a `View`, such as a struct with named props
+///
+/// Can be used in `view!` as an expression (`[value]`), or constructed with struct syntax
+/// (`@Card { title: "Hello" }`)
+pub trait Component {
+    fn render(self) -> View;
+}
+
+/// Prop of a component props builder which has not been given
+///
+/// Used by `#[component]`
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Unset;
+
+/// Prop of a component props builder which has been given
+///
+/// Used by `#[component]`
+#[derive(Clone, Copy, Debug)]
+pub struct Set<T>(pub T);
+
+/// Value of a prop in a component props builder, or the default value of the type if not given
+///
+/// Used by `#[component]`. A prop whose type does not implement `Default` must be given
+pub trait PropOrDefault<T> {
+    fn or_default(self) -> T;
+}
+impl<T> PropOrDefault<T> for Set<T> {
+    fn or_default(self) -> T {
+        self.0
+    }
+}
+impl<T> PropOrDefault<T> for Unset
+where
+    T: Default,
+{
+    fn or_default(self) -> T {
+        T::default()
+    }
+}
+
+/// Value of a prop in a component props builder, or a given default value if not given
+///
+/// Used by `#[component]`, for props with `#[prop(default = ...)]`
+pub trait PropOr<T> {
+    fn or_else(self, default: impl FnOnce() -> T) -> T;
+}
+impl<T> PropOr<T> for Set<T> {
+    fn or_else(self, _default: impl FnOnce() -> T) -> T {
+        self.0
+    }
+}
+impl<T> PropOr<T> for Unset {
+    fn or_else(self, default: impl FnOnce() -> T) -> T {
+        default()
+    }
 }
 
 /// Html-like element
@@ -23,19 +113,28 @@ pub struct Element {
 macro_rules! define_tag {
     ( $( $ident:ident $str:literal ),* $(,)? ) => {
         /// Html tag for `Element` and `DomElement`
-        #[derive(Clone, Copy, Debug)]
-        pub enum Tag { $(
-            /// Html tag
-            $ident,
-        )* }
-
-        impl From<Tag> for &'static str {
-            fn from(value: Tag) -> &'static str {
-                match value {
-                    $( Tag::$ident => $str,)*
+        #[derive(Clone, Debug, PartialEq, Eq)]
+        pub enum Tag {
+            $(
+                /// Html tag
+                $ident,
+            )*
+            /// Any other tag, such as a custom element (web component)
+            ///
+            /// Custom element names should contain a hyphen. Eg. `<my-widget>`
+            Custom(String),
+        }
+
+        impl Tag {
+            /// Get tag name as string
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $( Tag::$ident => $str, )*
+                    Tag::Custom(name) => name,
                 }
             }
         }
+        /// Only converts known tags, not `Tag::Custom`
         impl TryFrom<&str> for Tag {
             type Error = ();
             fn try_from(value: &str) -> Result<Self, Self::Error> {
@@ -49,12 +148,19 @@ macro_rules! define_tag {
 }
 
 define_tag! {
-    A "a", Abbr "abbr", Address "address", Article "article", Aside "aside", Audio "audio", B "b", Base "base", Bdi "bdi", Bdo "bdo", Blockquote "blockquote", Body "body", Br "br", Button "button", Caption "caption", Cite "cite", Code "code", Col "col", Colgroup "colgroup", Data "data", Datalist "datalist", Dd "dd", Del "del", Details "details", Dfn "dfn", Dialog "dialog", Div "div", Dl "dl", Dt "dt", Em "em", Embed "embed", Fieldset "fieldset", Figcaption "figcaption", Figure "figure", Footer "footer", Form "form", H1 "h1", H2 "h2", H3 "h3", H4 "h4", H5 "h5", H6 "h6", Head "head", Header "header", Hr "hr", Html "html", I "i", Iframe "iframe", Img "img", Input "input", Ins "ins", Kbd "kbd", Label "label", Legend "legend", Li "li", Link "link", Main "main", Map "map", Mark "mark", Meta "meta", Meter "meter", Nav "nav", Noscript "noscript", Object "object", Ol "ol", Optgroup "optgroup", Option "option", Output "output", P "p", Param "param", Picture "picture", Pre "pre", Progress "progress", Q "q", Rp "rp", Rt "rt", Ruby "ruby", S "s", Samp "samp", Script "script", Section "section", Select "select", Small "small", Source "source", Span "span", Strong "strong", Style "style", Sub "sub", Summary "summary", Sup "sup", Svg "svg", Table "table", Tbody "tbody", Td "td", Template "template", Textarea "textarea", Tfoot "tfoot", Th "th", Thead "thead", Time "time", Title "title", Tr "tr", Track "track", U "u", Ul "ul", Var "var", Video "video", Wbr "wbr"
+    A "a", Abbr "abbr", Address "address", Area "area", Article "article", Aside "aside", Audio "audio", B "b", Base "base", Bdi "bdi", Bdo "bdo", Blockquote "blockquote", Body "body", Br "br", Button "button", Canvas "canvas", Caption "caption", Cite "cite", Code "code", Col "col", Colgroup "colgroup", Data "data", Datalist "datalist", Dd "dd", Del "del", Details "details", Dfn "dfn", Dialog "dialog", Div "div", Dl "dl", Dt "dt", Em "em", Embed "embed", Fieldset "fieldset", Figcaption "figcaption", Figure "figure", Footer "footer", Form "form", H1 "h1", H2 "h2", H3 "h3", H4 "h4", H5 "h5", H6 "h6", Head "head", Header "header", Hgroup "hgroup", Hr "hr", Html "html", I "i", Iframe "iframe", Img "img", Input "input", Ins "ins", Kbd "kbd", Label "label", Legend "legend", Li "li", Link "link", Main "main", Map "map", Mark "mark", Menu "menu", Meta "meta", Meter "meter", Nav "nav", Noscript "noscript", Object "object", Ol "ol", Optgroup "optgroup", Option "option", Output "output", P "p", Param "param", Picture "picture", Pre "pre", Progress "progress", Q "q", Rp "rp", Rt "rt", Ruby "ruby", S "s", Samp "samp", Script "script", Search "search", Section "section", Select "select", Slot "slot", Small "small", Source "source", Span "span", Strong "strong", Style "style", Sub "sub", Summary "summary", Sup "sup", Svg "svg", Table "table", Tbody "tbody", Td "td", Template "template", Textarea "textarea", Tfoot "tfoot", Th "th", Thead "thead", Time "time", Title "title", Tr "tr", Track "track", U "u", Ul "ul", Var "var", Video "video", Wbr "wbr",
+    // SVG
+    Animate "animate", AnimateMotion "animateMotion", AnimateTransform "animateTransform", Circle "circle", ClipPath "clipPath", Defs "defs", Desc "desc", Ellipse "ellipse", FeBlend "feBlend", FeColorMatrix "feColorMatrix", FeComponentTransfer "feComponentTransfer", FeComposite "feComposite", FeConvolveMatrix "feConvolveMatrix", FeDiffuseLighting "feDiffuseLighting", FeDisplacementMap "feDisplacementMap", FeDistantLight "feDistantLight", FeDropShadow "feDropShadow", FeFlood "feFlood", FeFuncA "feFuncA", FeFuncB "feFuncB", FeFuncG "feFuncG", FeFuncR "feFuncR", FeGaussianBlur "feGaussianBlur", FeImage "feImage", FeMerge "feMerge", FeMergeNode "feMergeNode", FeMorphology "feMorphology", FeOffset "feOffset", FePointLight "fePointLight", FeSpecularLighting "feSpecularLighting", FeSpotLight "feSpotLight", FeTile "feTile", FeTurbulence "feTurbulence", Filter "filter", ForeignObject "foreignObject", G "g", Image "image", Line "line", LinearGradient "linearGradient", Marker "marker", Mask "mask", Metadata "metadata", Mpath "mpath", Path "path", Pattern "pattern", Polygon "polygon", Polyline "polyline", RadialGradient "radialGradient", Rect "rect", Set "set", Stop "stop", Switch "switch", Symbol "symbol", Text "text", TextPath "textPath", Tspan "tspan", Use "use", View "view",
+    // MathML
+    Annotation "annotation", AnnotationXml "annotation-xml", Math "math", Merror "merror", Mfrac "mfrac", Mi "mi", Mmultiscripts "mmultiscripts", Mn "mn", Mo "mo", Mover "mover", Mpadded "mpadded", Mphantom "mphantom", Mprescripts "mprescripts", Mroot "mroot", Mrow "mrow", Ms "ms", Mspace "mspace", Msqrt "msqrt", Mstyle "mstyle", Msub "msub", Msubsup "msubsup", Msup "msup", Mtable "mtable", Mtd "mtd", Mtext "mtext", Mtr "mtr", Munder "munder", Munderover "munderover", Semantics "semantics",
 }
 
 impl Tag {
     /// An element which does not need a trailing slash when self-closing
     ///
+    /// Only applies to html elements, not foreign (SVG or MathML) elements, which may always
+    /// self-close when empty
+    ///
     /// Example:
     ///     - `<br>` - Void element
     ///     - `<div/>` - Not a void element
@@ -62,9 +168,182 @@ impl Tag {
         use Tag::*;
         matches!(
             self,
-            Base | Br | Col | Embed | Hr | Img | Input | Link | Meta | Param | Source | Track | Wbr
+            Area | Base
+                | Br
+                | Col
+                | Embed
+                | Hr
+                | Img
+                | Input
+                | Link
+                | Meta
+                | Param
+                | Source
+                | Track
+                | Wbr
         )
     }
+
+    /// Create a tag from any name, using a known tag if possible
+    pub fn from_name(name: impl Into<String>) -> Self {
+        let name = name.into();
+        match name.as_str().try_into() {
+            Ok(tag) => tag,
+            Err(()) => Self::Custom(name),
+        }
+    }
+
+    /// Whether a string can be used as a tag name, without producing invalid html
+    ///
+    /// Must begin with an ascii letter, and follow the rules of attribute names
+    pub fn is_valid_name(name: &str) -> bool {
+        name.starts_with(|ch: char| ch.is_ascii_alphabetic()) && Attribute::is_valid_name(name)
+    }
+
+    /// An element whose text content is not parsed for html entities
+    ///
+    /// Text inside these elements is not escaped when rendered, other than to prevent the element
+    /// being closed early
+    pub fn is_raw_text(&self) -> bool {
+        use Tag::*;
+        matches!(self, Script | Style)
+    }
+
+    /// An element whose whitespace is significant, so must not be changed when rendering
+    pub fn is_preformatted(&self) -> bool {
+        use Tag::*;
+        matches!(self, Pre | Textarea | Script | Style)
+    }
+
+    /// An html element which is displayed inline by default, so surrounding whitespace affects
+    /// layout
+    ///
+    /// Custom elements are inline by default
+    pub fn is_inline(&self) -> bool {
+        use Tag::*;
+        matches!(
+            self,
+            A | Abbr
+                | Audio
+                | B
+                | Bdi
+                | Bdo
+                | Br
+                | Button
+                | Canvas
+                | Cite
+                | Code
+                | Data
+                | Del
+                | Dfn
+                | Em
+                | Embed
+                | I
+                | Iframe
+                | Img
+                | Input
+                | Ins
+                | Kbd
+                | Label
+                | Mark
+                | Math
+                | Meter
+                | Object
+                | Output
+                | Picture
+                | Progress
+                | Q
+                | Rp
+                | Rt
+                | Ruby
+                | S
+                | Samp
+                | Select
+                | Slot
+                | Small
+                | Span
+                | Strong
+                | Sub
+                | Sup
+                | Svg
+                | Textarea
+                | Time
+                | U
+                | Var
+                | Video
+                | Wbr
+                | Custom(_)
+        )
+    }
+
+    /// An svg element which renders its text content, so whitespace inside it affects layout
+    pub fn is_svg_text(&self) -> bool {
+        use Tag::*;
+        matches!(self, Text | TextPath | Tspan)
+    }
+}
+
+/// Namespace of an element, which affects how it is rendered
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Namespace {
+    /// Html elements
+    #[default]
+    Html,
+    /// Elements inside `<svg>`
+    Svg,
+    /// Elements inside `<math>`
+    MathMl,
+}
+
+impl Namespace {
+    /// Get namespace of an element with given tag, inside a parent of this namespace
+    ///
+    /// `<svg>` and `<math>` tags begin a new namespace
+    pub fn of_element(self, tag: &Tag) -> Self {
+        match (self, tag) {
+            (Self::Html, Tag::Svg) => Self::Svg,
+            (Self::Html, Tag::Math) => Self::MathMl,
+            _ => self,
+        }
+    }
+
+    /// Get namespace of the children of an element with given tag, in this namespace
+    ///
+    /// Text integration points (Eg. `<foreignObject>` or `<mtext>`) contain html elements
+    pub fn of_children(self, tag: &Tag) -> Self {
+        match (self, tag) {
+            (Self::Svg, Tag::ForeignObject) => Self::Html,
+            (Self::MathMl, Tag::Mi | Tag::Mo | Tag::Mn | Tag::Ms | Tag::Mtext) => Self::Html,
+            _ => self,
+        }
+    }
+
+    /// Whether elements are foreign (not html), and can self-close when empty
+    pub fn is_foreign(self) -> bool {
+        self != Self::Html
+    }
+}
+
+/// Wrapper for trusted markup, which should not be escaped when rendered
+///
+/// Converts into `Node::Raw`
+#[derive(Clone, Debug, Default)]
+pub struct PreEscaped<T>(pub T);
+
+/// Static nodes, which were rendered at compile time
+///
+/// Rendered as-is with default or minified options, without building any nodes.
+/// Indentation depends on the surrounding nodes, so the original nodes are built to indent
+#[derive(Clone, Copy, Debug)]
+pub struct StaticView {
+    /// Nodes rendered with default options
+    pub html: &'static str,
+    /// Nodes rendered with `RenderOptions::minified`, as children of an html element
+    ///
+    /// Not used inside preformatted or foreign elements, where `html` is used instead
+    pub minified: &'static str,
+    /// Build the nodes which `html` was rendered from
+    pub build: fn() -> View,
 }
 
 /// Html attribute for `Element` and `DomElement`
@@ -74,14 +353,293 @@ pub struct Attribute {
     pub value: Option<String>,
 }
 
+impl Attribute {
+    /// Whether a string can be used as an attribute name, without producing invalid html
+    ///
+    /// Must not be empty, and must not contain whitespace, control characters, quotes, `>`, `/`,
+    /// or `=`
+    pub fn is_valid_name(name: &str) -> bool {
+        !name.is_empty()
+            && !name.chars().any(|ch| {
+                ch.is_whitespace()
+                    || ch.is_control()
+                    || matches!(ch, '"' | '\'' | '>' | '/' | '=' | '<' | '\u{FFFD}')
+            })
+    }
+}
+
+/// Class names for the `class` attribute, without any duplicates
+///
+/// Displays as a space-separated list. Eg. `ClassList::new().with("card").with_if("active", true)`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ClassList(Vec<String>);
+
+impl ClassList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add class names, which may be space-separated, ignoring any which were already added
+    pub fn push(&mut self, classes: &str) {
+        for class in classes.split_whitespace() {
+            if !self.0.iter().any(|other| other == class) {
+                self.0.push(class.to_string());
+            }
+        }
+    }
+
+    /// Add class names, which may be space-separated
+    pub fn with(mut self, classes: impl AsRef<str>) -> Self {
+        self.push(classes.as_ref());
+        self
+    }
+    /// Add class names, which may be space-separated, if condition is `true`
+    pub fn with_if(self, classes: impl AsRef<str>, condition: bool) -> Self {
+        if condition {
+            self.with(classes)
+        } else {
+            self
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Each class name, in the order they were added
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(String::as_str)
+    }
+}
+
+/// Inline css declarations for the `style` attribute
+///
+/// Displays as `property: value` pairs, separated by `;`.
+/// Eg. `Style::new().with("width", "50%").with("color", None::<&str>)` is `width: 50%`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Style(Vec<(String, String)>);
+
+/// Value of a css property, or `None` to skip the property
+///
+/// Escaped when converted, so that it cannot end the declaration or add other properties
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StyleValue(pub Option<String>);
+
+impl Style {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set a property, replacing any earlier value, unless value is `None`
+    pub fn set(&mut self, property: &str, value: impl Into<StyleValue>) {
+        let Some(value) = value.into().0 else {
+            return;
+        };
+        let property = escape_css_name(property);
+        self.0.retain(|(other, _)| *other != property);
+        self.0.push((property, value));
+    }
+
+    /// Set a property, unless value is `None`
+    pub fn with(mut self, property: &str, value: impl Into<StyleValue>) -> Self {
+        self.set(property, value);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl StyleValue {
+    /// Escape a css value
+    pub fn new(value: &str) -> Self {
+        Self(Some(escape_css_value(value)))
+    }
+}
+
+/// Escape a css property name, which can only contain letters, digits, `-`, and `_`
+fn escape_css_name(name: &str) -> String {
+    let mut escaped = String::with_capacity(name.len());
+    for ch in name.chars() {
+        if ch.is_alphanumeric() || matches!(ch, '-' | '_') {
+            escaped.push(ch);
+        } else {
+            push_css_escape(&mut escaped, ch);
+        }
+    }
+    escaped
+}
+
+/// Escape any characters in a css value which would end the declaration, or add other properties
+///
+/// Strings and brackets are closed if they are not balanced
+fn escape_css_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    let mut depth = 0usize;
+    let mut quote = None;
+    let mut chars = value.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match (quote, ch) {
+            (_, ch) if ch.is_control() => push_css_escape(&mut escaped, ch),
+
+            // Inside string, only keep escapes of other characters
+            (Some(_), '\\') => match chars.next_if(|ch| !ch.is_control()) {
+                Some(next) => {
+                    escaped.push(ch);
+                    escaped.push(next);
+                }
+                None => push_css_escape(&mut escaped, ch),
+            },
+            (Some(end), ch) => {
+                if ch == end {
+                    quote = None;
+                }
+                escaped.push(ch);
+            }
+
+            (None, '"' | '\'') => {
+                quote = Some(ch);
+                escaped.push(ch);
+            }
+            (None, '(') => {
+                depth += 1;
+                escaped.push(ch);
+            }
+            (None, ')') if depth > 0 => {
+                depth -= 1;
+                escaped.push(ch);
+            }
+            // Start of comment
+            (None, '*') if escaped.ends_with('/') => push_css_escape(&mut escaped, ch),
+            (None, ')' | ';' | '{' | '}' | '\\') => push_css_escape(&mut escaped, ch),
+            (None, ch) => escaped.push(ch),
+        }
+    }
+    if let Some(end) = quote {
+        escaped.push(end);
+    }
+    escaped.extend((0..depth).map(|_| ')'));
+    escaped
+}
+
+/// Push a character as a css escape, which is its hex code
+fn push_css_escape(escaped: &mut String, ch: char) {
+    // Space ends the hex code
+    escaped.push_str(&format!("\\{:x} ", ch as u32));
+}
+
 // ---------------------
 // Handy implementations
 // ---------------------
 
+impl std::fmt::Display for ClassList {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0.join(" "))
+    }
+}
+impl<T> FromIterator<T> for ClassList
+where
+    T: AsRef<str>,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut classes = Self::new();
+        for class in iter {
+            classes.push(class.as_ref());
+        }
+        classes
+    }
+}
+impl From<&str> for ClassList {
+    fn from(value: &str) -> Self {
+        Self::new().with(value)
+    }
+}
+impl From<String> for ClassList {
+    fn from(value: String) -> Self {
+        Self::new().with(value)
+    }
+}
+
+impl std::fmt::Display for Style {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, (property, value)) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{}: {}", property, value)?;
+        }
+        Ok(())
+    }
+}
+impl From<&str> for StyleValue {
+    fn from(value: &str) -> Self {
+        Self::new(value)
+    }
+}
+impl From<String> for StyleValue {
+    fn from(value: String) -> Self {
+        Self::new(&value)
+    }
+}
+impl From<&String> for StyleValue {
+    fn from(value: &String) -> Self {
+        Self::new(value)
+    }
+}
+impl From<&&str> for StyleValue {
+    fn from(value: &&str) -> Self {
+        Self::new(value)
+    }
+}
+impl<T> From<Option<T>> for StyleValue
+where
+    T: Into<StyleValue>,
+{
+    fn from(value: Option<T>) -> Self {
+        value.map(Into::into).unwrap_or_default()
+    }
+}
+/// Used by `view!`, which does not move values
+impl From<&Option<String>> for StyleValue {
+    fn from(value: &Option<String>) -> Self {
+        value.as_ref().into()
+    }
+}
+impl From<&Option<&str>> for StyleValue {
+    fn from(value: &Option<&str>) -> Self {
+        value.as_ref().into()
+    }
+}
+/// Numbers cannot contain any characters which must be escaped
+macro_rules! impl_style_value_with_display {
+    ( $( $ty:ty ),* ) => {
+        $(
+            impl From<$ty> for StyleValue {
+                fn from(value: $ty) -> Self {
+                    Self(Some(value.to_string()))
+                }
+            }
+            impl From<&$ty> for StyleValue {
+                fn from(value: &$ty) -> Self {
+                    Self(Some(value.to_string()))
+                }
+            }
+            impl From<&Option<$ty>> for StyleValue {
+                fn from(value: &Option<$ty>) -> Self {
+                    value.as_ref().into()
+                }
+            }
+        )*
+    };
+}
+impl_style_value_with_display![
+    u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, usize, isize, f32, f64
+];
+
 impl std::fmt::Display for Tag {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let string: &str = (*self).into();
-        write!(f, "{}", string)
+        write!(f, "{}", self.as_str())
     }
 }
 
@@ -90,6 +648,14 @@ impl From<View> for Node {
         Self::Fragment(value)
     }
 }
+impl<T> From<T> for Node
+where
+    T: Component,
+{
+    fn from(value: T) -> Self {
+        Self::Fragment(value.render())
+    }
+}
 
 impl From<String> for Node {
     fn from(value: String) -> Self {
@@ -106,6 +672,11 @@ impl From<&str> for Node {
         Self::Text(value.to_string())
     }
 }
+impl From<&&str> for Node {
+    fn from(value: &&str) -> Self {
+        Self::Text(value.to_string())
+    }
+}
 impl From<char> for Node {
     fn from(value: char) -> Self {
         Self::Text(value.to_string())
@@ -116,6 +687,14 @@ impl From<&char> for Node {
         Self::Text(value.to_string())
     }
 }
+impl<T> From<PreEscaped<T>> for Node
+where
+    T: Into<String>,
+{
+    fn from(value: PreEscaped<T>) -> Self {
+        Self::Raw(value.0.into())
+    }
+}
 impl From<()> for Node {
     fn from(_: ()) -> Self {
         Self::Fragment(View(vec![]))
@@ -157,6 +736,14 @@ impl From<&str> for View {
         Self(vec![value.into()])
     }
 }
+impl<T> From<PreEscaped<T>> for View
+where
+    T: Into<String>,
+{
+    fn from(value: PreEscaped<T>) -> Self {
+        Self(vec![value.into()])
+    }
+}
 impl From<()> for View {
     fn from(_: ()) -> Self {
         View(vec![])
@@ -213,3 +800,64 @@ macro_rules! impl_with_display {
     };
 }
 impl_with_display![u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, usize, isize];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn css_value_cannot_end_declaration() {
+        assert_eq!(escape_css_value("50%"), "50%");
+        assert_eq!(escape_css_value("red; color: blue"), r"red\3b  color: blue");
+        assert_eq!(escape_css_value("red} body {"), r"red\7d  body \7b ");
+        assert_eq!(escape_css_value(r"a\b"), r"a\5c b");
+    }
+
+    #[test]
+    fn css_value_strings_and_brackets_are_balanced() {
+        assert_eq!(escape_css_value("\"a; b\""), "\"a; b\"");
+        assert_eq!(escape_css_value("'open"), "'open'");
+        assert_eq!(escape_css_value(r#""a\"; b"#), r#""a\"; b""#);
+        assert_eq!(escape_css_value("url(a.png"), "url(a.png)");
+        assert_eq!(escape_css_value("calc((1px)"), "calc((1px))");
+        assert_eq!(escape_css_value("a) b"), r"a\29  b");
+    }
+
+    #[test]
+    fn css_value_cannot_start_comment() {
+        assert_eq!(escape_css_value("red /* x"), r"red /\2a  x");
+        assert_eq!(escape_css_value("'/*'"), "'/*'");
+    }
+
+    #[test]
+    fn css_control_characters_are_escaped() {
+        assert_eq!(escape_css_value("a\nb"), r"a\a b");
+        assert_eq!(escape_css_value("'a\\\n"), r"'a\5c \a '");
+        assert_eq!(escape_css_name("a\tb"), r"a\9 b");
+    }
+
+    #[test]
+    fn css_name_is_escaped() {
+        assert_eq!(escape_css_name("background-color"), "background-color");
+        assert_eq!(escape_css_name("--accent_2"), "--accent_2");
+        assert_eq!(escape_css_name("color:red;x"), r"color\3a red\3b x");
+        assert_eq!(escape_css_name("a}"), r"a\7d ");
+    }
+
+    #[test]
+    fn none_style_values_are_skipped() {
+        let style = Style::new()
+            .with("width", "50%")
+            .with("color", None::<&str>)
+            .with("height", Some(2));
+        assert_eq!(style.to_string(), "width: 50%; height: 2");
+        assert!(Style::new().with("color", None::<String>).is_empty());
+
+        let style = Style::new()
+            .with("width", "1px")
+            .with("width", None::<&str>);
+        assert_eq!(style.to_string(), "width: 1px");
+        let style = Style::new().with("width", "1px").with("width", "2px");
+        assert_eq!(style.to_string(), "width: 2px");
+    }
+}
@@ -0,0 +1,103 @@
+use std::{any::Any, cell::RefCell, marker::PhantomData};
+
+thread_local! {
+    /// Values given with `enter` or `with_context`, innermost last
+    static CONTEXT: RefCell<Vec<Box<dyn Any>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Guard of a context value given with `enter`, which removes the value when dropped
+///
+/// Also removes any values given after this one, which have not yet been removed
+#[must_use = "context value is removed when guard is dropped"]
+pub struct ContextGuard {
+    /// Number of values before this one
+    depth: usize,
+    /// Context is thread-local, so guard must be dropped on the same thread
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for ContextGuard {
+    fn drop(&mut self) {
+        CONTEXT.with(|context| context.borrow_mut().truncate(self.depth));
+    }
+}
+
+/// Give a value which can be read with `use_context`, until the returned guard is dropped
+///
+/// Unlike `with_context`, this does not need a closure, so `?` and `return` can be used in the
+/// scope of the guard.
+/// This is used by `[:context value { ... }]` in `view!`
+///
+/// ```
+/// # use ibex_core::context::{enter, use_context};
+/// #[derive(Clone, PartialEq, Debug)]
+/// struct Theme(&'static str);
+///
+/// {
+///     let _guard = enter(Theme("dark"));
+///     assert_eq!(use_context::<Theme>(), Some(Theme("dark")));
+/// }
+/// assert_eq!(use_context::<Theme>(), None);
+/// ```
+pub fn enter<T>(value: T) -> ContextGuard
+where
+    T: Any,
+{
+    CONTEXT.with(|context| {
+        let mut context = context.borrow_mut();
+        let depth = context.len();
+        context.push(Box::new(value));
+        ContextGuard {
+            depth,
+            _not_send: PhantomData,
+        }
+    })
+}
+
+/// Call a function, with a value which can be read with `use_context`
+///
+/// Contexts can be nested. A value of the same type in an inner context overrides the outer value,
+/// until the inner function returns
+///
+/// Views are built when `view!` is evaluated, so components must be called inside the function.
+/// In `view!`, use `[:context value { ... }]` instead
+///
+/// ```
+/// # use ibex_core::context::{use_context, with_context};
+/// #[derive(Clone, PartialEq, Debug)]
+/// struct Theme(&'static str);
+///
+/// with_context(Theme("dark"), || {
+///     assert_eq!(use_context::<Theme>(), Some(Theme("dark")));
+///     with_context(Theme("light"), || {
+///         assert_eq!(use_context::<Theme>(), Some(Theme("light")));
+///     });
+///     assert_eq!(use_context::<Theme>(), Some(Theme("dark")));
+/// });
+/// assert_eq!(use_context::<Theme>(), None);
+/// ```
+pub fn with_context<T, R>(value: T, f: impl FnOnce() -> R) -> R
+where
+    T: Any,
+{
+    // Value is removed when function returns, or panics
+    let _guard = enter(value);
+    f()
+}
+
+/// Get a clone of the innermost context value of a type, if any
+///
+/// See `with_context`
+pub fn use_context<T>() -> Option<T>
+where
+    T: Any + Clone,
+{
+    CONTEXT.with(|context| {
+        context
+            .borrow()
+            .iter()
+            .rev()
+            .find_map(|value| value.downcast_ref::<T>())
+            .cloned()
+    })
+}
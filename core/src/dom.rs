@@ -1,114 +1,427 @@
-use crate::compose::{Attribute, Node, Tag, View};
-use crate::render::{render, render_nodes};
+use std::collections::HashMap;
+use std::{fmt, io};
+
+use crate::compose::{Attribute, DocumentTag, Element, Namespace, Node, StaticView, Tag, View};
+use crate::render::{
+    panic_if_invalid, render, render_document_to, render_nodes, render_to, render_to_io,
+    try_render, try_render_to, try_render_to_io, RenderOptions,
+};
+use crate::Error;
 
 /// HTML document to render to string for `.html` file
+///
+/// Converted to DOM nodes when rendered, borrowing from the `View`, so a document can be rendered
+/// many times without being cloned
 #[derive(Clone, Debug)]
 pub struct Document {
-    /// <html lang="...">
-    pub(super) lang: Option<String>,
-    /// <head>
-    pub(super) head: DomElement,
-    /// <body>
-    pub(super) body: DomElement,
+    /// Attributes of <html>. Eg. `lang`
+    ///
+    /// Attributes given in the view replace any with the same name
+    pub(super) attributes: Vec<Attribute>,
+    /// Contents of <head> and <body>
+    pub(super) view: View,
+}
+
+/// <head> and <body> nodes of a `Document`, borrowed from a `View`
+#[derive(Clone, Debug, Default)]
+pub(super) struct DomDocument<'a> {
+    /// Attributes of <html>, given in the view
+    pub(super) html_attributes: Vec<&'a Attribute>,
+    /// Attributes of <head>
+    pub(super) head_attributes: Vec<&'a Attribute>,
+    /// Attributes of <body>
+    pub(super) body_attributes: Vec<&'a Attribute>,
+    /// Children of <head>
+    pub(super) head: Vec<DomNode<'a>>,
+    /// Children of <body>
+    pub(super) body: Vec<DomNode<'a>>,
+    /// Children to add to the end of <body>
+    body_end: Vec<DomNode<'a>>,
 }
 
 /// HTML node
 #[derive(Clone, Debug)]
-pub(super) enum DomNode {
+pub(super) enum DomNode<'a> {
     /// `DomElement`
-    Element(DomElement),
+    Element(DomElement<'a>),
     /// Text node
-    Text(String),
+    Text(&'a str),
+    /// Pre-escaped markup
+    Raw(&'a str),
+    /// Static nodes, rendered at compile time
+    Static(&'a StaticView),
 }
 
 /// HTML element
 #[derive(Clone, Debug)]
-pub(super) struct DomElement {
-    pub(super) tag: Tag,
-    pub(super) attributes: Vec<Attribute>,
-    pub(super) children: Vec<DomNode>,
+pub(super) struct DomElement<'a> {
+    pub(super) tag: &'a Tag,
+    pub(super) attributes: &'a [Attribute],
+    pub(super) children: Vec<DomNode<'a>>,
 }
 
 /// Convert a `View` to a `Document`
 pub fn convert(view: View, lang: Option<String>) -> Document {
-    // Empty <head> to push elements onto
-    let mut head = DomElement {
-        tag: Tag::Head,
-        attributes: vec![],
-        children: vec![],
-    };
-
-    // Convert <body>
-    let body = DomElement {
-        tag: Tag::Body,
-        attributes: vec![],
-        children: convert_nodes(view, &mut head),
-    };
-
-    Document { lang, head, body }
-}
-
-/// Convert multiple nodes (as a `View`) to DOM nodes
-fn convert_nodes(view: View, head: &mut DomElement) -> Vec<DomNode> {
-    view.0
+    let attributes = lang
+        .map(|lang| Attribute {
+            name: "lang".to_string(),
+            value: Some(lang),
+        })
         .into_iter()
-        .map(|node| convert_node(node, head))
-        .collect::<Vec<_>>()
-        .concat()
+        .collect();
+    Document { attributes, view }
 }
 
-/// Convert multiple nodes (as a `View`) to DOM nodes
+/// Nodes built from static nodes of a view, for static nodes which cannot be rendered from their
+/// pre-rendered html
 ///
-/// Panics if any node is `HEAD`
-fn convert_nodes_headless(view: View) -> Vec<DomNode> {
-    view.0
-        .into_iter()
-        .map(convert_node_headless)
-        .collect::<Vec<_>>()
-        .concat()
+/// Only static nodes are built, so the rest of the view is borrowed instead of cloned
+#[derive(Debug, Default)]
+pub(super) struct BuiltStatics {
+    /// Built nodes, by address of the static node in the view
+    views: HashMap<*const StaticView, View>,
 }
 
-/// Convert a `Node` to `DomNode`s
-fn convert_node(node: Node, head: &mut DomElement) -> Vec<DomNode> {
-    match node {
-        // Add nodes to <head>
-        // Return nothing
-        Node::HeadAppend(view) => {
-            for node in view.0 {
-                let mut node = convert_node(node, head);
-                head.children.append(&mut node)
+impl BuiltStatics {
+    /// Build static nodes which are entries of <head>, so that duplicate entries can be found
+    ///
+    /// If `all`, build every static node, to render with indentation
+    pub(super) fn new(view: &View, all: bool) -> Self {
+        let mut statics = Self::default();
+        statics.build_in(view, all, false);
+        statics
+    }
+
+    /// `is_head_entry` is `true` if nodes are entries of <head>, not nested in an element
+    fn build_in(&mut self, view: &View, all: bool, is_head_entry: bool) {
+        for node in &view.0 {
+            match node {
+                Node::Static(view) if all || is_head_entry => {
+                    self.views.insert(view, build_static(view));
+                }
+                Node::HeadAppend(view) => self.build_in(view, all, true),
+                Node::BodyEndAppend(view) => self.build_in(view, all, false),
+                Node::Element(element) => self.build_in(&element.children, all, false),
+                Node::Fragment(view) => self.build_in(view, all, is_head_entry),
+                Node::Static(_) | Node::DocumentAttributes(_, _) | Node::Text(_) | Node::Raw(_) => {
+                }
+            }
+        }
+    }
+
+    /// Built nodes of a static node, if it must not be rendered from its html
+    fn get(&self, view: &StaticView) -> Option<&View> {
+        if self.views.is_empty() {
+            return None;
+        }
+        self.views.get(&(view as *const StaticView))
+    }
+}
+
+/// Build the nodes of a static node, including any static nodes it contains
+fn build_static(view: &StaticView) -> View {
+    let mut view = (view.build)();
+    build_static_in_place(&mut view);
+    view
+}
+
+/// Replace static nodes with the nodes they were rendered from, without cloning the view
+fn build_static_in_place(view: &mut View) {
+    for node in &mut view.0 {
+        match node {
+            Node::Static(view) => *node = Node::Fragment(build_static(view)),
+            Node::HeadAppend(view) | Node::BodyEndAppend(view) | Node::Fragment(view) => {
+                build_static_in_place(view)
+            }
+            Node::Element(element) => build_static_in_place(&mut element.children),
+            Node::DocumentAttributes(_, _) | Node::Text(_) | Node::Raw(_) => (),
+        }
+    }
+}
+
+/// Convert a `View` to <head> and <body> DOM nodes, borrowing from the view
+///
+/// Static nodes which are in `statics` are converted from their built nodes
+pub(super) fn convert_document<'a>(view: &'a View, statics: &'a BuiltStatics) -> DomDocument<'a> {
+    // Empty <head> and attributes to push onto
+    let mut document = DomDocument::default();
+    let mut body = Vec::new();
+    convert_nodes(view, statics, &mut document, &mut body);
+    let mut heads = Vec::new();
+    collect_heads(view, &mut heads);
+    for head in heads {
+        let mut head_nodes = Vec::new();
+        convert_nodes(head, statics, &mut document, &mut head_nodes);
+        document.head.append(&mut head_nodes);
+    }
+    body.append(&mut document.body_end);
+    document.body = body;
+    dedupe_head(&mut document.head);
+    document
+}
+
+/// Find the children of each `HEAD` in a view, in order
+fn collect_heads<'a>(view: &'a View, heads: &mut Vec<&'a View>) {
+    for node in &view.0 {
+        match node {
+            Node::HeadAppend(view) => {
+                heads.push(view);
+                collect_heads(view, heads);
             }
-            vec![]
+            Node::Element(element) => collect_heads(&element.children, heads),
+            Node::BodyEndAppend(view) | Node::Fragment(view) => collect_heads(view, heads),
+            Node::DocumentAttributes(_, _) | Node::Text(_) | Node::Raw(_) | Node::Static(_) => (),
+        }
+    }
+}
+
+/// Key of an entry in <head>, where only one entry with each key is kept
+#[derive(PartialEq)]
+enum HeadKey<'a> {
+    /// `<meta charset>`
+    Charset,
+    /// `<base>`
+    Base,
+    /// `<title>`
+    Title,
+    /// `<meta>` with attribute name and value. Eg. `name="description"`
+    Meta(&'static str, &'a str),
+    /// `<link>` with `rel` and `href`, or only `rel` if it must be unique. Eg. `canonical`
+    Link(&'a str, Option<&'a str>),
+    /// `<script>` with `src`
+    Script(&'a str),
+}
+
+impl<'a> HeadKey<'a> {
+    /// Key of a node in <head>, if it must not be duplicated
+    fn of(node: &DomNode<'a>) -> Option<Self> {
+        let DomNode::Element(element) = node else {
+            return None;
+        };
+        let attribute = |name: &str| {
+            element
+                .attributes
+                .iter()
+                .find(|attribute| attribute.name == name)
+                .map(|attribute| attribute.value.as_deref().unwrap_or_default())
+        };
+        match element.tag {
+            Tag::Title => Some(HeadKey::Title),
+            Tag::Base => Some(HeadKey::Base),
+            Tag::Meta if attribute("charset").is_some() => Some(HeadKey::Charset),
+            Tag::Meta => {
+                let (name, value) = ["name", "property", "http-equiv", "itemprop"]
+                    .into_iter()
+                    .find_map(|name| Some((name, attribute(name)?)))?;
+                if name == "property" && is_open_graph_array(value) {
+                    return None;
+                }
+                Some(HeadKey::Meta(name, value))
+            }
+            Tag::Link => match attribute("rel")? {
+                rel @ ("canonical" | "manifest") => Some(HeadKey::Link(rel, None)),
+                rel => Some(HeadKey::Link(rel, Some(attribute("href")?))),
+            },
+            Tag::Script => Some(HeadKey::Script(attribute("src")?)),
+            _ => None,
+        }
+    }
+
+    /// Entries with a lower priority must come first. Eg. charset must be in the first 1024 bytes
+    fn priority(key: Option<&Self>) -> u8 {
+        match key {
+            Some(HeadKey::Charset) => 0,
+            Some(HeadKey::Base) => 1,
+            _ => 2,
+        }
+    }
+}
+
+/// Whether an OpenGraph property can be given multiple times, such as `og:image` or `article:tag`
+///
+/// Includes structured properties of each item. Eg. `og:image:width`
+fn is_open_graph_array(property: &str) -> bool {
+    const ARRAYS: &[&str] = &[
+        "og:image",
+        "og:video",
+        "og:audio",
+        "og:locale:alternate",
+        "article:author",
+        "article:tag",
+        "book:author",
+        "book:tag",
+        "music:album",
+        "music:musician",
+        "music:song",
+        "video:actor",
+        "video:director",
+        "video:writer",
+        "video:tag",
+    ];
+    ARRAYS.iter().any(|array| {
+        property
+            .strip_prefix(array)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(':'))
+    })
+}
+
+/// Remove duplicate entries of <head>, keeping the last one in the position of the first, and move
+/// any entries which must come first
+fn dedupe_head(head: &mut Vec<DomNode<'_>>) {
+    let mut entries: Vec<(Option<HeadKey>, DomNode)> = Vec::new();
+    for node in head.drain(..) {
+        let key = HeadKey::of(&node);
+        if let Some(key) = &key {
+            if let Some(entry) = entries
+                .iter_mut()
+                .find(|(other, _)| other.as_ref() == Some(key))
+            {
+                entry.1 = node;
+                continue;
+            }
+        }
+        entries.push((key, node));
+    }
+    // Stable sort keeps the order of entries of the same priority
+    entries.sort_by_key(|(key, _)| HeadKey::priority(key.as_ref()));
+    head.extend(entries.into_iter().map(|(_, node)| node));
+}
+
+/// Convert multiple nodes (as a `View`) to DOM nodes, pushing onto `nodes`
+fn convert_nodes<'a>(
+    view: &'a View,
+    statics: &'a BuiltStatics,
+    document: &mut DomDocument<'a>,
+    nodes: &mut Vec<DomNode<'a>>,
+) {
+    for node in &view.0 {
+        convert_node(node, statics, document, nodes);
+    }
+}
+
+/// Convert multiple nodes (as a `View`) to DOM nodes, pushing onto `nodes`
+///
+/// Returns an error if any node is `HEAD`, `BODY_END`, or attributes of the document
+fn convert_nodes_headless<'a>(
+    view: &'a View,
+    statics: &'a BuiltStatics,
+    nodes: &mut Vec<DomNode<'a>>,
+) -> Result<(), Error> {
+    for node in &view.0 {
+        convert_node_headless(node, statics, nodes)?;
+    }
+    Ok(())
+}
+
+/// Convert a `Node` to `DomNode`s, pushing onto `nodes`
+fn convert_node<'a>(
+    node: &'a Node,
+    statics: &'a BuiltStatics,
+    document: &mut DomDocument<'a>,
+    nodes: &mut Vec<DomNode<'a>>,
+) {
+    match node {
+        // Nodes for <head> are converted separately, from `collect_heads`
+        // Push nothing
+        Node::HeadAppend(_) => (),
+
+        // Add nodes to end of <body>
+        // Push nothing
+        Node::BodyEndAppend(view) => {
+            let mut body_end_nodes = Vec::new();
+            convert_nodes(view, statics, document, &mut body_end_nodes);
+            document.body_end.append(&mut body_end_nodes);
+        }
+
+        // Add attributes to <html>, <head>, or <body>
+        // Push nothing
+        Node::DocumentAttributes(tag, attributes) => {
+            let document_attributes = match tag {
+                DocumentTag::Html => &mut document.html_attributes,
+                DocumentTag::Head => &mut document.head_attributes,
+                DocumentTag::Body => &mut document.body_attributes,
+            };
+            document_attributes.extend(attributes);
         }
 
         // Recursively convert `Element` to `DomElement`
-        Node::Element(element) => vec![DomNode::Element(DomElement {
-            tag: element.tag,
-            attributes: element.attributes,
-            children: convert_nodes(element.children, head),
-        })],
+        Node::Element(element) => {
+            let mut children = Vec::new();
+            convert_nodes(&element.children, statics, document, &mut children);
+            nodes.push(DomNode::Element(DomElement {
+                tag: &element.tag,
+                attributes: &element.attributes,
+                children,
+            }));
+        }
 
-        Node::Fragment(view) => convert_nodes(view, head),
-        Node::Text(text) => vec![DomNode::Text(text)],
+        Node::Fragment(view) => convert_nodes(view, statics, document, nodes),
+        Node::Text(text) => nodes.push(DomNode::Text(text)),
+        Node::Raw(html) => nodes.push(DomNode::Raw(html)),
+        Node::Static(view) => match statics.get(view) {
+            Some(built) => convert_nodes(built, statics, document, nodes),
+            None => nodes.push(DomNode::Static(view)),
+        },
     }
 }
 
-/// Convert a `Node` to `DomNode`s
+/// Convert a `Node` to `DomNode`s, pushing onto `nodes`
 ///
-/// Panics if any node is `HEAD`
-fn convert_node_headless(node: Node) -> Vec<DomNode> {
+/// Returns an error if any node is `HEAD`, `BODY_END`, or attributes of the document
+fn convert_node_headless<'a>(
+    node: &'a Node,
+    statics: &'a BuiltStatics,
+    nodes: &mut Vec<DomNode<'a>>,
+) -> Result<(), Error> {
     match node {
-        Node::HeadAppend(_) => panic!("Cannot use `HEAD` without rendering as `Document`"),
+        Node::HeadAppend(_) => return Err(Error::HeadWithoutDocument),
+        Node::BodyEndAppend(_) => return Err(Error::BodyEndWithoutDocument),
+        Node::DocumentAttributes(tag, _) => {
+            return Err(Error::DocumentAttributesWithoutDocument {
+                tag: tag.tag().to_string(),
+            })
+        }
 
         // Recursively convert `Element` to `DomElement`
-        Node::Element(element) => vec![DomNode::Element(DomElement {
-            tag: element.tag,
-            attributes: element.attributes,
-            children: convert_nodes_headless(element.children),
-        })],
+        Node::Element(element) => {
+            let mut children = Vec::new();
+            convert_nodes_headless(&element.children, statics, &mut children)?;
+            nodes.push(DomNode::Element(DomElement {
+                tag: &element.tag,
+                attributes: &element.attributes,
+                children,
+            }));
+        }
+
+        Node::Fragment(view) => convert_nodes_headless(view, statics, nodes)?,
+        Node::Text(text) => nodes.push(DomNode::Text(text)),
+        Node::Raw(html) => nodes.push(DomNode::Raw(html)),
+        Node::Static(view) => match statics.get(view) {
+            Some(built) => convert_nodes_headless(built, statics, nodes)?,
+            None => nodes.push(DomNode::Static(view)),
+        },
+    }
+    Ok(())
+}
 
-        Node::Fragment(view) => convert_nodes_headless(view),
-        Node::Text(text) => vec![DomNode::Text(text)],
+/// Clone a view, replacing static nodes with the nodes they were rendered from
+fn expand_static(view: &View) -> View {
+    View(view.0.iter().map(expand_static_node).collect())
+}
+
+/// Replace static nodes with the nodes they were rendered from
+fn expand_static_node(node: &Node) -> Node {
+    match node {
+        Node::HeadAppend(view) => Node::HeadAppend(expand_static(view)),
+        Node::BodyEndAppend(view) => Node::BodyEndAppend(expand_static(view)),
+        Node::Element(element) => Node::Element(Element {
+            tag: element.tag.clone(),
+            attributes: element.attributes.clone(),
+            children: expand_static(&element.children),
+        }),
+        Node::Fragment(view) => Node::Fragment(expand_static(view)),
+        Node::Static(view) => Node::Fragment((view.build)()),
+        node => node.clone(),
     }
 }
 
@@ -126,13 +439,130 @@ impl View {
         convert(self, None)
     }
 
+    /// Render as a full document, without converting to `Document`
+    ///
+    /// Same output as `.document(lang).render()`, but borrows the view instead of consuming it
+    pub fn render_document(&self, lang: Option<&str>) -> String {
+        self.render_document_with(lang, &RenderOptions::default())
+    }
+    /// Render as a full document with options, without converting to `Document`
+    pub fn render_document_with(&self, lang: Option<&str>, options: &RenderOptions) -> String {
+        self.try_render_document_with(lang, options)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+    /// Render as a full document, returning an error if it cannot be rendered as valid html
+    ///
+    /// See `render_document`
+    pub fn try_render_document(&self, lang: Option<&str>) -> Result<String, Error> {
+        self.try_render_document_with(lang, &RenderOptions::default())
+    }
+    /// Render as a full document with options, returning an error if it cannot be rendered as
+    /// valid html
+    ///
+    /// See `render_document`
+    pub fn try_render_document_with(
+        &self,
+        lang: Option<&str>,
+        options: &RenderOptions,
+    ) -> Result<String, Error> {
+        let attributes: Vec<_> = lang
+            .map(|lang| Attribute {
+                name: "lang".to_string(),
+                value: Some(lang.to_string()),
+            })
+            .into_iter()
+            .collect();
+        let mut string = String::new();
+        render_document_to(self, &attributes, options, &mut string)?;
+        Ok(string)
+    }
+
     /// Render nodes as string, without converting to `Document`
     ///
     /// Does not include `body`, `head`, or `html` tags.
     /// This only renders the view
-    pub fn render_orphan(self) -> String {
-        let nodes = convert_nodes_headless(self);
-        render_nodes(nodes)
+    ///
+    /// Panics if view contains `HEAD` or `BODY_END`, or cannot be rendered as valid html.
+    /// See `try_render_orphan`
+    pub fn render_orphan(&self) -> String {
+        self.render_orphan_with(&RenderOptions::default())
+    }
+    /// Render nodes as string with options, without converting to `Document`
+    ///
+    /// See `render_orphan`
+    pub fn render_orphan_with(&self, options: &RenderOptions) -> String {
+        self.try_render_orphan_with(options)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+    /// Render nodes as string, without converting to `Document`, returning an error if view
+    /// contains `HEAD` or `BODY_END`, or cannot be rendered as valid html
+    ///
+    /// See `render_orphan`
+    pub fn try_render_orphan(&self) -> Result<String, Error> {
+        self.try_render_orphan_with(&RenderOptions::default())
+    }
+    /// Render nodes as string with options, without converting to `Document`, returning an error
+    /// if view contains `HEAD` or `BODY_END`, or cannot be rendered as valid html
+    ///
+    /// See `render_orphan`
+    pub fn try_render_orphan_with(&self, options: &RenderOptions) -> Result<String, Error> {
+        let mut string = String::new();
+        self.try_render_orphan_to_with(options, &mut string)?;
+        Ok(string)
+    }
+
+    /// Render nodes without converting to `Document`, directly into a writer
+    ///
+    /// See `render_orphan`
+    pub fn render_orphan_to(&self, w: impl fmt::Write) -> fmt::Result {
+        self.render_orphan_to_with(&RenderOptions::default(), w)
+    }
+    /// Render nodes with options without converting to `Document`, directly into a writer
+    ///
+    /// See `render_orphan`
+    pub fn render_orphan_to_with(
+        &self,
+        options: &RenderOptions,
+        w: impl fmt::Write,
+    ) -> fmt::Result {
+        panic_if_invalid(self.try_render_orphan_to_with(options, w))
+    }
+    /// Render nodes with options without converting to `Document`, directly into a writer,
+    /// returning an error if view contains `HEAD` or `BODY_END`, or cannot be rendered as valid
+    /// html
+    ///
+    /// See `render_orphan`
+    pub fn try_render_orphan_to_with(
+        &self,
+        options: &RenderOptions,
+        mut w: impl fmt::Write,
+    ) -> Result<(), Error> {
+        let statics = BuiltStatics::new(self, options.needs_built_statics());
+        let mut nodes = Vec::new();
+        convert_nodes_headless(self, &statics, &mut nodes)?;
+        render_nodes(&nodes, options, Namespace::Html, &mut w)
+    }
+
+    /// Replace static nodes (rendered at compile time) with the nodes they were rendered from
+    ///
+    /// Eg. to inspect or modify the nodes of a view
+    pub fn expand_static(&self) -> View {
+        expand_static(self)
+    }
+
+    /// Render nodes as string with options, as children of an element of the given namespace
+    ///
+    /// Used by `view!` to render static nodes at compile time
+    ///
+    /// Panics if view contains `HEAD` or `BODY_END`, or cannot be rendered as valid html
+    pub fn render_orphan_in(&self, namespace: Namespace, options: &RenderOptions) -> String {
+        let mut string = String::new();
+        let statics = BuiltStatics::default();
+        let mut nodes = Vec::new();
+        convert_nodes_headless(self, &statics, &mut nodes)
+            .and_then(|()| render_nodes(&nodes, options, namespace, &mut string))
+            .unwrap_or_else(|error| panic!("{}", error));
+        string
     }
 }
 impl From<View> for Document {
@@ -141,7 +571,81 @@ impl From<View> for Document {
     }
 }
 impl Document {
-    pub fn render(self) -> String {
+    pub fn render(&self) -> String {
         render(self)
     }
+    /// Render with options, such as indentation
+    pub fn render_with(&self, options: &RenderOptions) -> String {
+        self.try_render_with(options)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+    /// Render, returning an error if document cannot be rendered as valid html
+    ///
+    /// Useful for servers, where an invalid page should not panic
+    pub fn try_render(&self) -> Result<String, Error> {
+        try_render(self)
+    }
+    /// Render with options, returning an error if document cannot be rendered as valid html
+    pub fn try_render_with(&self, options: &RenderOptions) -> Result<String, Error> {
+        let mut string = String::new();
+        try_render_to(self, options, &mut string)?;
+        Ok(string)
+    }
+
+    /// Render directly into a writer, without building an intermediate string
+    pub fn render_to(&self, w: impl fmt::Write) -> fmt::Result {
+        render_to(self, &RenderOptions::default(), w)
+    }
+    /// Render with options directly into a writer, without building an intermediate string
+    pub fn render_to_with(&self, options: &RenderOptions, w: impl fmt::Write) -> fmt::Result {
+        render_to(self, options, w)
+    }
+
+    /// Render directly into an IO writer, such as a file or network stream
+    ///
+    /// Writes many small pieces, so consider using a buffered writer
+    pub fn render_to_io(&self, w: impl io::Write) -> io::Result<()> {
+        render_to_io(self, &RenderOptions::default(), w)
+    }
+    /// Render with options directly into an IO writer, such as a file or network stream
+    ///
+    /// Writes many small pieces, so consider using a buffered writer
+    pub fn render_to_io_with(&self, options: &RenderOptions, w: impl io::Write) -> io::Result<()> {
+        render_to_io(self, options, w)
+    }
+    /// Render with options directly into an IO writer, returning an error if document cannot be
+    /// rendered as valid html, or writing fails
+    ///
+    /// Anything written before the error is left in the writer
+    pub fn try_render_to_io_with(
+        &self,
+        options: &RenderOptions,
+        w: impl io::Write,
+    ) -> Result<(), Error> {
+        try_render_to_io(self, options, w)
+    }
+
+    /// Add an attribute to <html>, replacing any with the same name
+    ///
+    /// Eg. `dir`, or `class` for a dark theme
+    pub fn attribute(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.set_attribute(Attribute {
+            name: name.into(),
+            value: Some(value.into()),
+        });
+        self
+    }
+    fn set_attribute(&mut self, attribute: Attribute) {
+        self.attributes.retain(|other| other.name != attribute.name);
+        self.attributes.push(attribute);
+    }
+
+    /// Attributes of <html>, not including any given in the view
+    pub fn attributes(&self) -> &[Attribute] {
+        &self.attributes
+    }
+    /// Contents of <head> and <body>
+    pub fn view(&self) -> &View {
+        &self.view
+    }
 }
@@ -0,0 +1,88 @@
+use std::{fmt, io};
+
+/// Error while rendering, if a view cannot be rendered as valid html
+///
+/// Returned by `try_render` methods. Other render methods panic instead
+#[derive(Debug)]
+pub enum Error {
+    /// Tag name would produce invalid html
+    InvalidTagName {
+        /// Tag name, as given
+        tag: String,
+    },
+    /// Attribute name would produce invalid html
+    InvalidAttributeName {
+        /// Element which has the attribute
+        tag: String,
+        /// Attribute name, as given
+        name: String,
+    },
+    /// Void element (Eg. `<br>`) has children, which cannot be rendered
+    VoidWithChildren {
+        /// Tag name of void element
+        tag: String,
+    },
+    /// `HEAD` was used in a view rendered without a document (Eg. with `render_orphan`)
+    HeadWithoutDocument,
+    /// `BODY_END` was used in a view rendered without a document
+    BodyEndWithoutDocument,
+    /// Attributes of <html>, <head>, or <body> were given in a view rendered without a document
+    DocumentAttributesWithoutDocument {
+        /// Tag name of element
+        tag: String,
+    },
+    /// Writing to a formatter failed
+    Fmt(fmt::Error),
+    /// Writing to an IO writer failed
+    Io(io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidTagName { tag } => write!(f, "Invalid tag name {:?}", tag),
+            Self::InvalidAttributeName { tag, name } => {
+                write!(f, "Invalid attribute name {:?} on <{}> element", name, tag)
+            }
+            Self::VoidWithChildren { tag } => {
+                write!(f, "Void tag <{}> cannot contain children", tag)
+            }
+            Self::HeadWithoutDocument => {
+                write!(f, "Cannot use `HEAD` without rendering as `Document`")
+            }
+            Self::BodyEndWithoutDocument => {
+                write!(f, "Cannot use `BODY_END` without rendering as `Document`")
+            }
+            Self::DocumentAttributesWithoutDocument { tag } => {
+                write!(
+                    f,
+                    "Cannot set attributes of <{}> without rendering as `Document`",
+                    tag
+                )
+            }
+            Self::Fmt(error) => write!(f, "Failed to write html: {}", error),
+            Self::Io(error) => write!(f, "Failed to write html: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Fmt(error) => Some(error),
+            Self::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<fmt::Error> for Error {
+    fn from(value: fmt::Error) -> Self {
+        Self::Fmt(value)
+    }
+}
+impl From<io::Error> for Error {
+    fn from(value: io::Error) -> Self {
+        Self::Io(value)
+    }
+}
@@ -1,6 +1,15 @@
 /// Construct an abstract component structure
 pub mod compose;
+/// Pass values implicitly to components
+pub mod context;
 /// Convert components to structure one-to-one with HTML DOM tree
 pub mod dom;
 /// Render DOM tree to string
 pub mod render;
+
+/// Error while rendering
+mod error;
+pub use error::Error;
+
+/// Shrink rendered text, for minified output
+mod minify;
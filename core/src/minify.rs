@@ -0,0 +1,206 @@
+use std::borrow::Cow;
+
+/// Replace each run of whitespace with a single space
+///
+/// Browsers collapse whitespace in text anyway, except inside preformatted elements
+pub(super) fn collapse_whitespace(text: &str) -> Cow<'_, str> {
+    let has_run = text
+        .as_bytes()
+        .windows(2)
+        .any(|pair| is_whitespace(pair[0] as char) && is_whitespace(pair[1] as char));
+    let has_other = text.chars().any(|ch| is_whitespace(ch) && ch != ' ');
+    if !has_run && !has_other {
+        return Cow::Borrowed(text);
+    }
+
+    let mut collapsed = String::with_capacity(text.len());
+    let mut was_whitespace = false;
+    for ch in text.chars() {
+        if is_whitespace(ch) {
+            if !was_whitespace {
+                collapsed.push(' ');
+            }
+            was_whitespace = true;
+        } else {
+            collapsed.push(ch);
+            was_whitespace = false;
+        }
+    }
+    Cow::Owned(collapsed)
+}
+
+/// Html whitespace characters
+///
+/// Does not include non-breaking spaces, which are not collapsed
+fn is_whitespace(ch: char) -> bool {
+    matches!(ch, ' ' | '\t' | '\n' | '\r' | '\x0C')
+}
+
+/// Remove comments and unnecessary whitespace from a stylesheet
+///
+/// Strings are kept unchanged
+pub(super) fn minify_css(css: &str) -> String {
+    let mut minified = String::with_capacity(css.len());
+    let mut chars = css.chars().peekable();
+    let mut space_pending = false;
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            // Comment counts as whitespace
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = None;
+                for ch in chars.by_ref() {
+                    if previous == Some('*') && ch == '/' {
+                        break;
+                    }
+                    previous = Some(ch);
+                }
+                space_pending = true;
+            }
+
+            ch if ch.is_whitespace() => space_pending = true,
+
+            ch => {
+                // Space is only needed between tokens which could otherwise join together
+                // Not removed before `(` (Eg. `and (...)` in media queries), or around `+` and
+                // `-` (Eg. in `calc(...)`), or before `:` in a selector (Eg. `div :hover`)
+                let last = minified.chars().next_back();
+                if space_pending
+                    && last.is_some_and(|last| {
+                        !matches!(last, '{' | '}' | ';' | ':' | ',' | '>' | '~' | '(')
+                    })
+                    && !matches!(ch, '{' | '}' | ';' | ',' | '>' | '~' | ')')
+                    && !(ch == ':' && is_declaration_colon(chars.clone()))
+                {
+                    minified.push(' ');
+                }
+                space_pending = false;
+
+                // Last declaration in block does not need a semicolon
+                if ch == '}' && minified.ends_with(';') {
+                    minified.pop();
+                }
+                minified.push(ch);
+
+                // Copy string until closing quote
+                if ch == '"' || ch == '\'' {
+                    while let Some(next) = chars.next() {
+                        minified.push(next);
+                        if next == '\\' {
+                            if let Some(escaped) = chars.next() {
+                                minified.push(escaped);
+                            }
+                        } else if next == ch {
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+    }
+    minified
+}
+
+/// Whether a `:` ends the property name of a declaration, given the rest of the stylesheet after
+/// it, rather than starting a pseudo-class in a selector
+///
+/// A selector is followed by a block, and a declaration ends with `;` or `}`
+fn is_declaration_colon(rest: impl Iterator<Item = char>) -> bool {
+    for ch in rest {
+        match ch {
+            '{' => return false,
+            ';' | '}' => return true,
+            _ => (),
+        }
+    }
+    true
+}
+
+/// Remove indentation and blank lines from a script
+///
+/// Line breaks are kept, so automatic semicolon insertion is not affected.
+/// Scripts containing template literals or line continuations are only trimmed, as their strings
+/// can span multiple lines
+pub(super) fn minify_js(js: &str) -> String {
+    // Line break after a backslash may be `\r\n`
+    if js.contains('`') || js.contains("\\\n") || js.contains("\\\r") {
+        return js.trim().to_string();
+    }
+    js.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn whitespace_is_collapsed() {
+        assert_eq!(collapse_whitespace("a b"), "a b");
+        assert!(matches!(collapse_whitespace("a b"), Cow::Borrowed(_)));
+        assert_eq!(collapse_whitespace("  a \n\t b\n"), " a b ");
+        assert_eq!(collapse_whitespace("a\nb"), "a b");
+        assert_eq!(collapse_whitespace("a\u{a0}\u{a0}b"), "a\u{a0}\u{a0}b");
+    }
+
+    #[test]
+    fn css_whitespace_and_comments_are_removed() {
+        assert_eq!(
+            minify_css("body {\n  color: red;\n  margin: 0 auto;\n}\n"),
+            "body{color:red;margin:0 auto}"
+        );
+        assert_eq!(minify_css("a /* link */ > b , i{x:1}"), "a>b,i{x:1}");
+        assert_eq!(minify_css("p { color : red ; }"), "p{color:red}");
+    }
+
+    #[test]
+    fn css_space_is_kept_where_significant() {
+        assert_eq!(minify_css("div :hover { x: 1 }"), "div :hover{x:1}");
+        assert_eq!(
+            minify_css("a { div :hover { x: 1 } }"),
+            "a{div :hover{x:1}}"
+        );
+        assert_eq!(
+            minify_css("@media screen and (min-width: 1px) { }"),
+            "@media screen and (min-width:1px){}"
+        );
+        assert_eq!(
+            minify_css("p { width: calc(1px + 2%) }"),
+            "p{width:calc(1px + 2%)}"
+        );
+    }
+
+    #[test]
+    fn css_strings_are_kept() {
+        assert_eq!(
+            minify_css("p::before { content: \"a  /* b */ ;\" }"),
+            "p::before{content:\"a  /* b */ ;\"}"
+        );
+        assert_eq!(
+            minify_css("p { content: 'it\\'s  } ' }"),
+            "p{content:'it\\'s  } '}"
+        );
+    }
+
+    #[test]
+    fn js_indentation_and_blank_lines_are_removed() {
+        assert_eq!(
+            minify_js("\n  let a = 1\n\n  if (a) {\r\n    run()\r\n  }\n"),
+            "let a = 1\nif (a) {\nrun()\n}"
+        );
+    }
+
+    #[test]
+    fn js_multiline_strings_are_kept() {
+        let template = "  let a = `x\n    y`\n";
+        assert_eq!(minify_js(template), template.trim());
+        let continuation = "  let a = 'x\\\n    y'\n";
+        assert_eq!(minify_js(continuation), continuation.trim());
+        let continuation = "  let a = 'x\\\r\n    y'\r\n";
+        assert_eq!(minify_js(continuation), continuation.trim());
+    }
+}
@@ -1,88 +1,1062 @@
-use crate::compose::Attribute;
-use crate::dom::{Document, DomElement, DomNode};
+use std::fmt::{self, Write};
+use std::io;
+
+use std::borrow::Cow;
+
+use crate::compose::{Attribute, ClassList, Namespace, Tag, View};
+use crate::dom::{convert_document, BuiltStatics, Document, DomElement, DomNode};
+use crate::minify::{collapse_whitespace, minify_css, minify_js};
+use crate::Error;
+
+/// Options for how html is rendered
+///
+/// Default options render everything on a single line
+#[derive(Clone, Debug, Default)]
+pub struct RenderOptions {
+    /// Indent nested elements on new lines, with this many spaces
+    ///
+    /// Whitespace is only added where it does not change the layout of the page: not inside
+    /// inline elements, not between inline siblings, and not inside preformatted elements (Eg.
+    /// `<pre>`)
+    pub indent: Option<usize>,
+    /// Make output as small as possible, without changing how the page is displayed
+    ///
+    /// Collapses whitespace in text (except in preformatted elements), removes quotes from
+    /// attribute values where possible, omits optional closing tags, and minifies inline
+    /// `<style>` and `<script>` contents. Overrides `indent`
+    pub minify: bool,
+}
+
+impl RenderOptions {
+    /// Render everything on a single line
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Render with indented elements, for readability
+    pub fn pretty(indent: usize) -> Self {
+        Self {
+            indent: Some(indent),
+            ..Self::default()
+        }
+    }
+
+    /// Render with as few bytes as possible, for production
+    pub fn minified() -> Self {
+        Self {
+            minify: true,
+            ..Self::default()
+        }
+    }
+
+    /// Whether static nodes, rendered at compile time, must be built to render
+    ///
+    /// Indentation depends on the surrounding nodes, so it cannot be rendered at compile time
+    pub(super) fn needs_built_statics(&self) -> bool {
+        self.indent.is_some() && !self.minify
+    }
+}
 
 /// Render a `Document` to a HTML string
-pub fn render(page: Document) -> String {
-    if !page.head.attributes.is_empty() || !page.body.attributes.is_empty() {
-        panic!("Cannot use attributes on <head> or <body> tags (how did you even get this error?)");
-    }
-
-    format!(
-        concat!(
-            r"<!DOCTYPE html>",
-            r"<html{lang}>",
-            r"<head>",
-            r"{}",
-            r"</head>",
-            r"<body>",
-            r"{}",
-            r"</body>",
-            r"</html>",
-        ),
-        // ignores attributes!
-        render_nodes(page.head.children),
-        render_nodes(page.body.children),
-        lang = match page.lang {
-            Some(lang) => format!(" lang=\"{}\"", lang),
-            None => "".to_string(),
-        },
-    )
+///
+/// Panics if document cannot be rendered as valid html. See `try_render`
+pub fn render(page: &Document) -> String {
+    try_render(page).unwrap_or_else(|error| panic!("{}", error))
+}
+
+/// Render a `Document` to a HTML string, returning an error if it cannot be rendered as valid html
+pub fn try_render(page: &Document) -> Result<String, Error> {
+    let mut string = String::new();
+    try_render_to(page, &RenderOptions::default(), &mut string)?;
+    Ok(string)
+}
+
+/// Render a `Document` as HTML, directly into a writer
+///
+/// Panics if document cannot be rendered as valid html. See `try_render_to`
+pub fn render_to(page: &Document, options: &RenderOptions, w: impl Write) -> fmt::Result {
+    panic_if_invalid(try_render_to(page, options, w))
 }
 
-/// Render multiple DOM nodes
-pub(super) fn render_nodes(nodes: Vec<DomNode>) -> String {
-    nodes
-        .into_iter()
-        .map(render_node)
-        .collect::<Vec<_>>()
-        .join("")
+/// Render a `Document` as HTML, directly into a writer, returning an error if it cannot be
+/// rendered as valid html
+///
+/// Anything written before the error is left in the writer
+pub fn try_render_to(page: &Document, options: &RenderOptions, w: impl Write) -> Result<(), Error> {
+    render_document_to(&page.view, &page.attributes, options, w)
 }
-/// Render a single DOM node
-fn render_node(node: DomNode) -> String {
-    match node {
-        DomNode::Element(element) => render_element(element),
-        DomNode::Text(text) => text,
+
+/// Render a `Document` as HTML, directly into an IO writer, such as a file
+///
+/// Panics if document cannot be rendered as valid html. See `try_render_to_io`
+pub fn render_to_io(page: &Document, options: &RenderOptions, w: impl io::Write) -> io::Result<()> {
+    match try_render_to_io(page, options, w) {
+        Ok(()) => Ok(()),
+        Err(Error::Io(error)) => Err(error),
+        Err(error) => panic!("{}", error),
+    }
+}
+
+/// Render a `Document` as HTML, directly into an IO writer, returning an error if it cannot be
+/// rendered as valid html
+///
+/// Anything written before the error is left in the writer
+pub fn try_render_to_io(
+    page: &Document,
+    options: &RenderOptions,
+    w: impl io::Write,
+) -> Result<(), Error> {
+    let mut adapter = IoAdapter {
+        inner: w,
+        error: None,
+    };
+    match try_render_to(page, options, &mut adapter) {
+        Err(Error::Fmt(_)) => {
+            Err(Error::Io(adapter.error.unwrap_or_else(|| {
+                io::Error::other("failed to render document")
+            })))
+        }
+        result => result,
+    }
+}
+
+/// Panic if html could not be rendered, otherwise keep any error from the writer
+pub(super) fn panic_if_invalid(result: Result<(), Error>) -> fmt::Result {
+    match result {
+        Ok(()) => Ok(()),
+        Err(Error::Fmt(error)) => Err(error),
+        Err(error) => panic!("{}", error),
+    }
+}
+
+/// Render a `View` as a full HTML document, directly into a writer
+pub(super) fn render_document_to(
+    view: &View,
+    html_attributes: &[Attribute],
+    options: &RenderOptions,
+    w: impl Write,
+) -> Result<(), Error> {
+    // Static nodes are built only if they cannot be rendered from their html
+    let statics = BuiltStatics::new(view, options.needs_built_statics());
+    let document = convert_document(view, &statics);
+    let mut renderer = Renderer::new(options, w);
+
+    renderer.w.write_str("<!DOCTYPE html>")?;
+    renderer.newline()?;
+    renderer.w.write_str("<html")?;
+    let html_attributes =
+        merge_attributes(html_attributes.iter().chain(document.html_attributes), true);
+    renderer.render_merged_attributes(&Tag::Html, &html_attributes)?;
+    renderer.w.write_str(">")?;
+
+    renderer.depth += 1;
+    renderer.newline()?;
+    renderer.w.write_str("<head")?;
+    let head_attributes = merge_attributes(document.head_attributes, true);
+    renderer.render_merged_attributes(&Tag::Head, &head_attributes)?;
+    renderer.w.write_str(">")?;
+    renderer.render_children(&document.head, Namespace::Html, Some(&Tag::Head), true)?;
+    renderer.w.write_str("</head>")?;
+    renderer.newline()?;
+    renderer.w.write_str("<body")?;
+    let body_attributes = merge_attributes(document.body_attributes, true);
+    renderer.render_merged_attributes(&Tag::Body, &body_attributes)?;
+    renderer.w.write_str(">")?;
+    renderer.render_children(&document.body, Namespace::Html, Some(&Tag::Body), true)?;
+    renderer.w.write_str("</body>")?;
+    renderer.depth -= 1;
+
+    renderer.newline()?;
+    renderer.w.write_str("</html>")?;
+    if renderer.is_pretty() {
+        renderer.w.write_str("\n")?;
+    }
+    Ok(())
+}
+
+/// Merge all `class` attributes into the position of the first
+///
+/// If `replace`, keep only the last of any other attributes with the same name, in the position of
+/// the first
+fn merge_attributes<'a>(
+    attributes: impl IntoIterator<Item = &'a Attribute>,
+    replace: bool,
+) -> Vec<Cow<'a, Attribute>> {
+    let mut merged: Vec<Cow<Attribute>> = Vec::new();
+    for attribute in attributes {
+        let other = merged.iter_mut().find(|other| other.name == attribute.name);
+        match other {
+            Some(other) if attribute.name == "class" => {
+                let classes =
+                    ClassList::from_iter([&other.value, &attribute.value].into_iter().flatten());
+                other.to_mut().value = Some(classes.to_string());
+            }
+            Some(other) if replace => *other = Cow::Borrowed(attribute),
+            _ => merged.push(Cow::Borrowed(attribute)),
+        }
     }
+    merged
 }
 
-/// Render a DOM element to HTML string
-fn render_element(element: DomElement) -> String {
-    if element.tag.is_void() {
-        if !element.children.is_empty() {
-            panic!("Void tag <{}> cannot contain children", element.tag);
+/// Render multiple DOM nodes, without any parent element
+pub(super) fn render_nodes(
+    nodes: &[DomNode],
+    options: &RenderOptions,
+    namespace: Namespace,
+    w: &mut impl Write,
+) -> Result<(), Error> {
+    let mut renderer = Renderer::new(options, w);
+    if renderer.can_indent_children(nodes) {
+        for (i, node) in nodes.iter().enumerate() {
+            if i > 0 {
+                renderer.newline()?;
+            }
+            renderer.render_node(node, namespace, None, nodes.get(i + 1))?;
         }
-        format!(
-            "<{tag}{attrs}>",
-            tag = element.tag,
-            attrs = format_attributes(element.attributes),
-        )
+        Ok(())
     } else {
-        format!(
-            "<{tag}{attrs}>{content}</{tag}>",
-            tag = element.tag,
-            attrs = format_attributes(element.attributes),
-            content = render_nodes(element.children),
-        )
+        renderer.render_nodes(nodes, namespace, None)
+    }
+}
+
+/// Allows a `fmt::Write` to write to an `io::Write`, keeping the underlying IO error
+struct IoAdapter<W> {
+    inner: W,
+    error: Option<io::Error>,
+}
+
+impl<W: io::Write> Write for IoAdapter<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|error| {
+            self.error = Some(error);
+            fmt::Error
+        })
+    }
+}
+
+/// Renders DOM nodes into a writer, keeping track of indentation
+struct Renderer<'a, W> {
+    w: W,
+    options: &'a RenderOptions,
+    /// Current indentation level
+    depth: usize,
+    /// Whether inside a preformatted element, where no whitespace can be added
+    is_preformatted: bool,
+}
+
+impl<'a, W: Write> Renderer<'a, W> {
+    fn new(options: &'a RenderOptions, w: W) -> Self {
+        Self {
+            w,
+            options,
+            depth: 0,
+            is_preformatted: false,
+        }
+    }
+
+    fn is_pretty(&self) -> bool {
+        self.options.indent.is_some() && !self.options.minify && !self.is_preformatted
+    }
+
+    /// Write a new line, and indentation for current depth
+    ///
+    /// Does nothing, unless rendering with indentation
+    fn newline(&mut self) -> Result<(), Error> {
+        if !self.is_pretty() {
+            return Ok(());
+        }
+        let indent = self.options.indent.unwrap_or_default();
+        self.w.write_str("\n")?;
+        for _ in 0..indent * self.depth {
+            self.w.write_char(' ')?;
+        }
+        Ok(())
+    }
+
+    /// Whether whitespace can be added between nodes, without affecting the layout
+    ///
+    /// Only if every node is an element which is not inline (Eg. not text)
+    fn can_indent_children(&self, nodes: &[DomNode]) -> bool {
+        self.is_pretty()
+            && nodes.iter().all(|node| match node {
+                DomNode::Element(element) => !element.tag.is_inline(),
+                DomNode::Text(_) | DomNode::Raw(_) | DomNode::Static(_) => false,
+            })
+    }
+
+    /// Render children of an element, on indented lines if possible
+    ///
+    /// `parent` is `None` if the nodes are not rendered inside a known element (Eg. orphans).
+    /// `is_block` is `false` if whitespace inside the parent element would affect the layout
+    fn render_children(
+        &mut self,
+        nodes: &[DomNode],
+        namespace: Namespace,
+        parent: Option<&Tag>,
+        is_block: bool,
+    ) -> Result<(), Error> {
+        if nodes.is_empty() || !is_block || !self.can_indent_children(nodes) {
+            return self.render_nodes(nodes, namespace, parent);
+        }
+
+        self.depth += 1;
+        for (i, node) in nodes.iter().enumerate() {
+            self.newline()?;
+            self.render_node(node, namespace, parent, nodes.get(i + 1))?;
+        }
+        self.depth -= 1;
+        self.newline()
+    }
+
+    /// Render multiple DOM nodes, without adding whitespace between them
+    fn render_nodes(
+        &mut self,
+        nodes: &[DomNode],
+        namespace: Namespace,
+        parent: Option<&Tag>,
+    ) -> Result<(), Error> {
+        for (i, node) in nodes.iter().enumerate() {
+            self.render_node(node, namespace, parent, nodes.get(i + 1))?;
+        }
+        Ok(())
+    }
+
+    /// Render a single DOM node
+    ///
+    /// `parent` and `next` (the next sibling) decide whether a closing tag can be omitted
+    fn render_node(
+        &mut self,
+        node: &DomNode,
+        namespace: Namespace,
+        parent: Option<&Tag>,
+        next: Option<&DomNode>,
+    ) -> Result<(), Error> {
+        match node {
+            DomNode::Element(element) => {
+                let omit_end_tag = self.options.minify
+                    && !namespace.of_element(element.tag).is_foreign()
+                    && can_omit_end_tag(element.tag, parent, next);
+                self.render_element(element, namespace, omit_end_tag)?;
+            }
+            DomNode::Text(text) if self.options.minify && !self.is_preformatted => {
+                write_escaped_text(&mut self.w, &collapse_whitespace(text))?;
+            }
+            DomNode::Text(text) => write_escaped_text(&mut self.w, text)?,
+            DomNode::Raw(html) => self.w.write_str(html)?,
+            // Minified html was rendered for html elements, where whitespace can be collapsed
+            DomNode::Static(view)
+                if self.options.minify && !self.is_preformatted && !namespace.is_foreign() =>
+            {
+                self.w.write_str(view.minified)?;
+            }
+            DomNode::Static(view) => self.w.write_str(view.html)?,
+        }
+        Ok(())
+    }
+
+    /// Render children of a raw text element (`<script>` or `<style>`)
+    ///
+    /// Text is not escaped, as entities are not parsed inside these elements
+    fn render_raw_text_nodes(&mut self, element: &DomElement) -> Result<(), Error> {
+        for (i, node) in element.children.iter().enumerate() {
+            match node {
+                DomNode::Text(text) => {
+                    let text = if self.options.minify {
+                        minify_raw_text(element, text)
+                    } else {
+                        Cow::Borrowed(*text)
+                    };
+                    write_escaped_raw_text(&mut self.w, &text)?
+                }
+                // Minified html would collapse whitespace of any text
+                DomNode::Static(view) => self.w.write_str(view.html)?,
+                node => self.render_node(
+                    node,
+                    Namespace::Html,
+                    Some(element.tag),
+                    element.children.get(i + 1),
+                )?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Render a DOM element as HTML
+    ///
+    /// `namespace` is the namespace of the parent element.
+    /// `omit_end_tag` is `true` if the closing tag is optional, and can be left out
+    fn render_element(
+        &mut self,
+        element: &DomElement,
+        namespace: Namespace,
+        omit_end_tag: bool,
+    ) -> Result<(), Error> {
+        if !Tag::is_valid_name(element.tag.as_str()) {
+            return Err(Error::InvalidTagName {
+                tag: element.tag.to_string(),
+            });
+        }
+        let namespace = namespace.of_element(element.tag);
+
+        write!(self.w, "<{}", element.tag)?;
+        let is_last_unquoted = self.render_attributes(element.tag, element.attributes)?;
+
+        if namespace.is_foreign() {
+            // Foreign elements can always self-close
+            if element.children.is_empty() {
+                // Slash would otherwise be parsed as part of an unquoted value
+                if is_last_unquoted {
+                    self.w.write_str(" ")?;
+                }
+                return Ok(self.w.write_str("/>")?);
+            }
+        } else if element.tag.is_void() {
+            if !element.children.is_empty() {
+                return Err(Error::VoidWithChildren {
+                    tag: element.tag.to_string(),
+                });
+            }
+            return Ok(self.w.write_str(">")?);
+        }
+        self.w.write_str(">")?;
+
+        let children_namespace = namespace.of_children(element.tag);
+        if !namespace.is_foreign() && element.tag.is_raw_text() {
+            self.render_raw_text_nodes(element)?;
+        } else if element.tag.is_preformatted() {
+            let was_preformatted = self.is_preformatted;
+            self.is_preformatted = true;
+            self.render_nodes(&element.children, children_namespace, Some(element.tag))?;
+            self.is_preformatted = was_preformatted;
+        } else {
+            // Whitespace is not significant between foreign elements, other than inside svg text
+            let is_block = if namespace.is_foreign() {
+                !element.tag.is_svg_text()
+            } else {
+                !element.tag.is_inline()
+            };
+            self.render_children(
+                &element.children,
+                children_namespace,
+                Some(element.tag),
+                is_block,
+            )?;
+        }
+
+        if omit_end_tag {
+            return Ok(());
+        }
+        write!(self.w, "</{}>", element.tag)?;
+        Ok(())
+    }
+
+    /// Render attributes in key="value" format
+    ///
+    /// Any `class` attributes are merged into one.
+    /// Returns `true` if the value of the last attribute was written without quotes
+    fn render_attributes(&mut self, tag: &Tag, attributes: &[Attribute]) -> Result<bool, Error> {
+        let class_count = attributes
+            .iter()
+            .filter(|attribute| attribute.name == "class")
+            .count();
+        if class_count > 1 {
+            let merged = merge_attributes(attributes, false);
+            return self.render_merged_attributes(tag, &merged);
+        }
+        let mut is_unquoted = false;
+        for attribute in attributes {
+            // Space to separate from tag name
+            self.w.write_str(" ")?;
+            is_unquoted = self.render_attribute(tag, attribute)?;
+        }
+        Ok(is_unquoted)
+    }
+
+    /// Render attributes from `merge_attributes`, each with a leading space
+    ///
+    /// Returns `true` if the value of the last attribute was written without quotes
+    fn render_merged_attributes(
+        &mut self,
+        tag: &Tag,
+        attributes: &[Cow<Attribute>],
+    ) -> Result<bool, Error> {
+        let mut is_unquoted = false;
+        for attribute in attributes {
+            self.w.write_str(" ")?;
+            is_unquoted = self.render_attribute(tag, attribute)?;
+        }
+        Ok(is_unquoted)
+    }
+
+    /// Render a single attribute of an element with the given tag, escaping the value
+    ///
+    /// Returns `true` if the value was written without quotes.
+    /// Returns an error if attribute name is invalid
+    fn render_attribute(&mut self, tag: &Tag, attribute: &Attribute) -> Result<bool, Error> {
+        if !Attribute::is_valid_name(&attribute.name) {
+            return Err(Error::InvalidAttributeName {
+                tag: tag.to_string(),
+                name: attribute.name.clone(),
+            });
+        }
+        self.w.write_str(&attribute.name)?;
+        match &attribute.value {
+            // Empty value is the same as no value
+            Some(value) if self.options.minify && value.is_empty() => (),
+            Some(value) if self.options.minify && can_unquote_attribute_value(value) => {
+                self.w.write_str("=")?;
+                write_escaped_attribute_value(&mut self.w, value)?;
+                return Ok(true);
+            }
+            Some(value) => {
+                self.w.write_str("=\"")?;
+                write_escaped_attribute_value(&mut self.w, value)?;
+                self.w.write_str("\"")?;
+            }
+            None => (),
+        }
+        Ok(false)
+    }
+}
+
+/// Whether the closing tag of a html element is optional, and can be omitted without changing the
+/// DOM tree
+///
+/// `next` is the next sibling node, or `None` if the element is the last child of `parent`.
+/// `parent` is `None` if the element has no known parent (Eg. orphans), so the content after the
+/// element is unknown
+fn can_omit_end_tag(tag: &Tag, parent: Option<&Tag>, next: Option<&DomNode>) -> bool {
+    use Tag::*;
+    let next = match next {
+        Some(DomNode::Element(element)) => Some(element.tag),
+        // Text might start with whitespace, and raw markup is unknown
+        Some(DomNode::Text(_) | DomNode::Raw(_) | DomNode::Static(_)) => return false,
+        None if parent.is_none() => return false,
+        None => None,
+    };
+    let is_last = next.is_none();
+    let next_is = |tags: &[Tag]| next.is_some_and(|next| tags.contains(next));
+
+    match tag {
+        Li => is_last || next_is(&[Li]),
+        Dt => next_is(&[Dt, Dd]),
+        Dd => is_last || next_is(&[Dt, Dd]),
+        Rt | Rp => is_last || next_is(&[Rt, Rp]),
+        Optgroup => is_last || next_is(&[Optgroup]),
+        Option => is_last || next_is(&[Option, Optgroup]),
+        Thead => next_is(&[Tbody, Tfoot]),
+        Tbody => is_last || next_is(&[Tbody, Tfoot]),
+        Tfoot => is_last,
+        Tr => is_last || next_is(&[Tr]),
+        Td | Th => is_last || next_is(&[Td, Th]),
+        P => match next {
+            Some(next) => matches!(
+                next,
+                Address
+                    | Article
+                    | Aside
+                    | Blockquote
+                    | Details
+                    | Dialog
+                    | Div
+                    | Dl
+                    | Fieldset
+                    | Figcaption
+                    | Figure
+                    | Footer
+                    | Form
+                    | H1
+                    | H2
+                    | H3
+                    | H4
+                    | H5
+                    | H6
+                    | Header
+                    | Hgroup
+                    | Hr
+                    | Main
+                    | Menu
+                    | Nav
+                    | Ol
+                    | P
+                    | Pre
+                    | Search
+                    | Section
+                    | Table
+                    | Ul
+            ),
+            // Content after parent would become part of the paragraph
+            None => !matches!(
+                parent,
+                Some(A | Audio | Del | Ins | Map | Noscript | Video | Custom(_))
+            ),
+        },
+        _ => false,
+    }
+}
+
+/// Whether an attribute value can be written without quotes
+fn can_unquote_attribute_value(value: &str) -> bool {
+    !value.is_empty()
+        && !value
+            .chars()
+            .any(|ch| ch.is_ascii_whitespace() || matches!(ch, '"' | '\'' | '=' | '<' | '>' | '`'))
+}
+
+/// Minify the text of a raw text element, if the language is known
+fn minify_raw_text<'a>(element: &DomElement, text: &'a str) -> Cow<'a, str> {
+    match element.tag {
+        Tag::Style => Cow::Owned(minify_css(text)),
+        Tag::Script => {
+            let script_type = element
+                .attributes
+                .iter()
+                .find(|attribute| attribute.name.eq_ignore_ascii_case("type"))
+                .and_then(|attribute| attribute.value.as_deref())
+                .unwrap_or_default();
+            // Other types (Eg. json, templates) are left unchanged
+            if matches!(
+                script_type.to_ascii_lowercase().as_str(),
+                "" | "module" | "text/javascript" | "application/javascript"
+            ) {
+                Cow::Owned(minify_js(text))
+            } else {
+                Cow::Borrowed(text)
+            }
+        }
+        _ => Cow::Borrowed(text),
+    }
+}
+
+/// Escape text content, so it cannot be parsed as markup
+///
+/// Replaces `&`, `<`, and `>` with html entities
+pub fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    write_escaped_text(&mut escaped, text).expect("writing to string should not fail");
+    escaped
+}
+
+/// Escape a double-quoted attribute value
+///
+/// Replaces `&`, `<`, and `"` with html entities
+pub fn escape_attribute_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    write_escaped_attribute_value(&mut escaped, value).expect("writing to string should not fail");
+    escaped
+}
+
+/// Escape text content of a raw text element, so it cannot close the element early
+///
+/// Replaces `</` with `<\/`, and the `<` of `<!--` and `<script` (case-insensitive) with `\u003c`,
+/// which are equivalent in javascript strings, regular expressions, and JSON.
+/// Otherwise, `<!--<script>` would stop the next `</script>` from closing the element
+pub fn escape_raw_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    write_escaped_raw_text(&mut escaped, text).expect("writing to string should not fail");
+    escaped
+}
+
+/// Write text content, escaped with `escape_text`
+fn write_escaped_text(w: &mut impl Write, text: &str) -> fmt::Result {
+    write_escaped(w, text, |ch| match ch {
+        '&' => Some("&amp;"),
+        '<' => Some("&lt;"),
+        '>' => Some("&gt;"),
+        _ => None,
+    })
+}
+
+/// Write attribute value, escaped with `escape_attribute_value`
+fn write_escaped_attribute_value(w: &mut impl Write, value: &str) -> fmt::Result {
+    write_escaped(w, value, |ch| match ch {
+        '&' => Some("&amp;"),
+        '<' => Some("&lt;"),
+        '"' => Some("&quot;"),
+        _ => None,
+    })
+}
+
+/// Write raw text content, escaped with `escape_raw_text`
+fn write_escaped_raw_text(w: &mut impl Write, text: &str) -> fmt::Result {
+    let starts_with = |rest: &str, prefix: &str| {
+        rest.get(..prefix.len())
+            .is_some_and(|start| start.eq_ignore_ascii_case(prefix))
+    };
+    let mut start = 0;
+    for (i, _) in text.match_indices('<') {
+        let rest = &text[i + 1..];
+        let replacement = if rest.starts_with('/') {
+            "<\\"
+        } else if starts_with(rest, "!--") || starts_with(rest, "script") {
+            "\\u003c"
+        } else {
+            continue;
+        };
+        w.write_str(&text[start..i])?;
+        w.write_str(replacement)?;
+        start = i + 1;
     }
+    w.write_str(&text[start..])
 }
 
-/// Render attributes in key="value" format
-fn format_attributes(attributes: Vec<Attribute>) -> String {
-    if attributes.is_empty() {
-        return String::new();
+/// Write a string, replacing some characters
+///
+/// Unchanged sections are written as whole slices, rather than by character
+fn write_escaped(
+    w: &mut impl Write,
+    text: &str,
+    replace: impl Fn(char) -> Option<&'static str>,
+) -> fmt::Result {
+    let mut start = 0;
+    for (i, ch) in text.char_indices() {
+        if let Some(replacement) = replace(ch) {
+            w.write_str(&text[start..i])?;
+            w.write_str(replacement)?;
+            start = i + ch.len_utf8();
+        }
     }
-    // Space to separate from tag name
-    " ".to_string()
-        + &attributes
-            .into_iter()
-            .map(format_attribute_value)
-            .collect::<Vec<_>>()
-            .join(" ")
+    w.write_str(&text[start..])
 }
 
-fn format_attribute_value(attribute: Attribute) -> String {
-    match attribute.value {
-        Some(value) => attribute.name + "=\"" + &value + "\"",
-        None => attribute.name,
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compose::{Element, Node};
+
+    fn element(tag: Tag, children: Vec<Node>) -> Node {
+        Node::Element(Element {
+            tag,
+            attributes: Vec::new(),
+            children: View(children),
+        })
+    }
+
+    #[test]
+    fn text_is_escaped() {
+        assert_eq!(
+            escape_text("<b>Tom & Jerry</b>"),
+            "&lt;b&gt;Tom &amp; Jerry&lt;/b&gt;"
+        );
+        assert_eq!(
+            escape_text("\"quotes\" are 'fine'"),
+            "\"quotes\" are 'fine'"
+        );
+
+        let view = View(vec![element(
+            Tag::P,
+            vec![Node::Text("<script>alert(1)</script>".to_string())],
+        )]);
+        assert_eq!(
+            view.render_orphan(),
+            "<p>&lt;script&gt;alert(1)&lt;/script&gt;</p>"
+        );
+    }
+
+    #[test]
+    fn raw_nodes_are_not_escaped() {
+        let view = View(vec![element(
+            Tag::P,
+            vec![Node::Raw("<b>&amp;</b>".to_string())],
+        )]);
+        assert_eq!(view.render_orphan(), "<p><b>&amp;</b></p>");
+    }
+
+    #[test]
+    fn raw_text_cannot_close_element() {
+        assert_eq!(
+            escape_raw_text("let s = \"</script><script>alert(1)\";"),
+            "let s = \"<\\/script>\\u003cscript>alert(1)\";"
+        );
+        assert_eq!(
+            escape_raw_text("<!-- <Script> </script> <scripts> <!- <"),
+            "\\u003c!-- \\u003cScript> <\\/script> \\u003cscripts> <!- <"
+        );
+        assert_eq!(
+            escape_raw_text("if (a < b && c > d) {}"),
+            "if (a < b && c > d) {}"
+        );
+
+        let view = View(vec![element(
+            Tag::Script,
+            vec![Node::Text("x = \"</SCRIPT>\" && 1 < 2".to_string())],
+        )]);
+        assert_eq!(
+            view.render_orphan(),
+            "<script>x = \"<\\/SCRIPT>\" && 1 < 2</script>"
+        );
+    }
+
+    #[test]
+    fn attribute_value_is_escaped() {
+        assert_eq!(
+            escape_attribute_value(r#"Say "hi" & <wave>"#),
+            "Say &quot;hi&quot; &amp; &lt;wave>"
+        );
+
+        let view = View(vec![Node::Element(Element {
+            tag: Tag::Div,
+            attributes: vec![Attribute {
+                name: "title".to_string(),
+                value: Some(r#"" onclick="alert(1)"#.to_string()),
+            }],
+            children: View::default(),
+        })]);
+        assert_eq!(
+            view.render_orphan(),
+            r#"<div title="&quot; onclick=&quot;alert(1)"></div>"#
+        );
+    }
+
+    #[test]
+    fn invalid_names_are_rejected() {
+        let view = View(vec![element(
+            Tag::Custom("my-tag onclick=x".to_string()),
+            vec![],
+        )]);
+        assert!(matches!(
+            view.try_render_orphan(),
+            Err(Error::InvalidTagName { tag }) if tag == "my-tag onclick=x"
+        ));
+
+        for name in ["on click", "a\"b", "x>", "x/", "", "x=y"] {
+            let view = View(vec![Node::Element(Element {
+                tag: Tag::Div,
+                attributes: vec![Attribute {
+                    name: name.to_string(),
+                    value: None,
+                }],
+                children: View::default(),
+            })]);
+            assert!(
+                matches!(
+                    view.clone().document_no_lang().try_render(),
+                    Err(Error::InvalidAttributeName { .. })
+                ),
+                "attribute name {:?} should be rejected",
+                name
+            );
+            assert!(view.try_render_orphan().is_err());
+        }
+    }
+
+    #[test]
+    fn minified_foreign_element_self_closes_after_unquoted_value() {
+        let options = RenderOptions {
+            minify: true,
+            ..Default::default()
+        };
+        let attribute = |name: &str, value: &str| Attribute {
+            name: name.to_string(),
+            value: Some(value.to_string()),
+        };
+        let view = View(vec![Node::Element(Element {
+            tag: Tag::Svg,
+            attributes: Vec::new(),
+            children: View(vec![
+                Node::Element(Element {
+                    tag: Tag::Path,
+                    attributes: vec![attribute("d", "M0")],
+                    children: View::default(),
+                }),
+                Node::Element(Element {
+                    tag: Tag::Use,
+                    attributes: vec![attribute("xlink:href", "#g/")],
+                    children: View::default(),
+                }),
+                Node::Element(Element {
+                    tag: Tag::Circle,
+                    attributes: vec![attribute("r", "1"), attribute("fill", "red blue")],
+                    children: View::default(),
+                }),
+            ]),
+        })]);
+        assert_eq!(
+            view.render_orphan_with(&options),
+            r#"<svg><path d=M0 /><use xlink:href=#g/ /><circle r=1 fill="red blue"/></svg>"#
+        );
+    }
+
+    fn text(text: &str) -> Node {
+        Node::Text(text.to_string())
+    }
+
+    #[test]
+    fn block_children_are_indented() {
+        let view = View(vec![element(
+            Tag::Ul,
+            vec![
+                element(Tag::Li, vec![text("One")]),
+                element(Tag::Li, vec![element(Tag::Em, vec![text("Two")])]),
+            ],
+        )]);
+        assert_eq!(
+            view.render_orphan_with(&RenderOptions::pretty(2)),
+            "<ul>\n  <li>One</li>\n  <li><em>Two</em></li>\n</ul>"
+        );
+    }
+
+    #[test]
+    fn inline_siblings_are_not_indented() {
+        let view = View(vec![element(
+            Tag::Div,
+            vec![
+                element(Tag::P, vec![text("Block")]),
+                element(Tag::Span, vec![text("Inline")]),
+            ],
+        )]);
+        assert_eq!(
+            view.render_orphan_with(&RenderOptions::pretty(2)),
+            "<div><p>Block</p><span>Inline</span></div>"
+        );
+
+        let view = View(vec![element(
+            Tag::P,
+            vec![text("Hello "), element(Tag::Em, vec![text("world")])],
+        )]);
+        assert_eq!(
+            view.render_orphan_with(&RenderOptions::pretty(2)),
+            "<p>Hello <em>world</em></p>"
+        );
+    }
+
+    #[test]
+    fn preformatted_children_are_not_indented() {
+        let view = View(vec![element(
+            Tag::Div,
+            vec![element(
+                Tag::Pre,
+                vec![element(Tag::Div, vec![element(Tag::P, vec![text("  a")])])],
+            )],
+        )]);
+        assert_eq!(
+            view.render_orphan_with(&RenderOptions::pretty(2)),
+            "<div>\n  <pre><div><p>  a</p></div></pre>\n</div>"
+        );
+
+        let view = View(vec![element(
+            Tag::Textarea,
+            vec![element(Tag::P, vec![text("  b\n")])],
+        )]);
+        assert_eq!(
+            view.render_orphan_with(&RenderOptions::pretty(2)),
+            "<textarea><p>  b\n</p></textarea>"
+        );
+    }
+
+    #[test]
+    fn svg_text_children_are_not_indented() {
+        let view = View(vec![element(
+            Tag::Svg,
+            vec![element(
+                Tag::Text,
+                vec![
+                    element(Tag::Tspan, vec![text("One")]),
+                    element(Tag::TextPath, vec![element(Tag::Tspan, vec![text("Two")])]),
+                ],
+            )],
+        )]);
+        assert_eq!(
+            view.render_orphan_with(&RenderOptions::pretty(2)),
+            "<svg>\n  <text><tspan>One</tspan><textPath><tspan>Two</tspan></textPath></text>\n</svg>"
+        );
+    }
+
+    #[test]
+    fn head_and_body_are_indented() {
+        let view = View(vec![
+            Node::HeadAppend(View(vec![element(Tag::Title, vec![text("Page")])])),
+            element(Tag::Main, vec![element(Tag::P, vec![text("Body")])]),
+        ]);
+        assert_eq!(
+            view.render_document_with(Some("en"), &RenderOptions::pretty(2)),
+            "<!DOCTYPE html>\n<html lang=\"en\">\n  <head>\n    <title>Page</title>\n  </head>\n  \
+                <body>\n    <main>\n      <p>Body</p>\n    </main>\n  </body>\n</html>\n"
+        );
+    }
+
+    #[test]
+    fn end_tag_is_omitted_before_known_siblings() {
+        fn dom(tag: &Tag) -> DomNode<'_> {
+            DomNode::Element(DomElement {
+                tag,
+                attributes: &[],
+                children: Vec::new(),
+            })
+        }
+        let ul = Some(&Tag::Ul);
+        assert!(can_omit_end_tag(&Tag::Li, ul, Some(&dom(&Tag::Li))));
+        assert!(can_omit_end_tag(&Tag::Li, ul, None));
+        assert!(!can_omit_end_tag(&Tag::Li, ul, Some(&dom(&Tag::Div))));
+        assert!(can_omit_end_tag(
+            &Tag::Dt,
+            Some(&Tag::Dl),
+            Some(&dom(&Tag::Dd))
+        ));
+        assert!(!can_omit_end_tag(&Tag::Dt, Some(&Tag::Dl), None));
+        assert!(can_omit_end_tag(
+            &Tag::Thead,
+            Some(&Tag::Table),
+            Some(&dom(&Tag::Tbody))
+        ));
+        assert!(!can_omit_end_tag(&Tag::Thead, Some(&Tag::Table), None));
+        assert!(!can_omit_end_tag(&Tag::Div, Some(&Tag::Body), None));
+
+        // Paragraph is closed by a block, but not by inline content
+        let body = Some(&Tag::Body);
+        assert!(can_omit_end_tag(&Tag::P, body, Some(&dom(&Tag::Ul))));
+        assert!(!can_omit_end_tag(&Tag::P, body, Some(&dom(&Tag::Span))));
+        assert!(can_omit_end_tag(&Tag::P, body, None));
+        assert!(!can_omit_end_tag(&Tag::P, Some(&Tag::A), None));
+        assert!(!can_omit_end_tag(
+            &Tag::P,
+            Some(&Tag::Custom("x-card".to_string())),
+            None
+        ));
+    }
+
+    #[test]
+    fn end_tag_is_kept_before_unknown_content() {
+        let ul = Some(&Tag::Ul);
+        assert!(!can_omit_end_tag(&Tag::Li, ul, Some(&DomNode::Text(" "))));
+        assert!(!can_omit_end_tag(&Tag::Li, ul, Some(&DomNode::Raw("<li>"))));
+        // Orphans are followed by unknown content
+        assert!(!can_omit_end_tag(&Tag::Li, None, None));
+        assert!(!can_omit_end_tag(&Tag::P, None, None));
+    }
+
+    #[test]
+    fn class_attributes_are_merged() {
+        let attribute = |name: &str, value: Option<&str>| Attribute {
+            name: name.to_string(),
+            value: value.map(str::to_string),
+        };
+        let attributes = [
+            attribute("class", Some("a b")),
+            attribute("lang", Some("en")),
+            attribute("class", None),
+            attribute("class", Some("b c")),
+            attribute("lang", Some("fr")),
+        ];
+        let pairs = |merged: Vec<Cow<Attribute>>| {
+            merged
+                .iter()
+                .map(|attribute| (attribute.name.clone(), attribute.value.clone()))
+                .collect::<Vec<_>>()
+        };
+        let pair = |name: &str, value: &str| (name.to_string(), Some(value.to_string()));
+
+        assert_eq!(
+            pairs(merge_attributes(&attributes, false)),
+            [
+                pair("class", "a b c"),
+                pair("lang", "en"),
+                pair("lang", "fr")
+            ]
+        );
+        assert_eq!(
+            pairs(merge_attributes(&attributes, true)),
+            [pair("class", "a b c"), pair("lang", "fr")]
+        );
     }
 }
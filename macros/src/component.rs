@@ -0,0 +1,344 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote, ToTokens};
+use syn::visit_mut::{self, VisitMut};
+use syn::{
+    parse_quote, Expr, FnArg, GenericParam, Generics, Ident, ItemFn, Lifetime, LifetimeParam,
+    ParenthesizedGenericArguments, Pat, TypeBareFn, TypeReference,
+};
+
+/// Name of the lifetime given to elided references in parameter types
+const PROPS_LIFETIME: &str = "'props";
+
+/// Component function, which takes a generated props struct
+pub struct Component {
+    function: ItemFn,
+    props: Ident,
+    /// Generics of function, with the props lifetime added if any parameter borrows
+    generics: Generics,
+    fields: Vec<Prop>,
+}
+
+/// Parameter of a component function, as a field of the props struct
+struct Prop {
+    /// Pattern of parameter, which includes `mut`
+    pattern: Pat,
+    ident: Ident,
+    ty: syn::Type,
+    /// Default value, from `#[prop(default = expr)]`
+    default: Option<Expr>,
+}
+
+impl ToTokens for Component {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let Component {
+            function,
+            props,
+            generics,
+            fields,
+        } = self;
+        let ItemFn {
+            attrs,
+            vis,
+            sig,
+            block,
+        } = function;
+        let name = &sig.ident;
+        let (constness, asyncness, unsafety, abi) =
+            (&sig.constness, &sig.asyncness, &sig.unsafety, &sig.abi);
+        let output = &sig.output;
+        let statements = &block.stmts;
+        let (impl_generics, type_generics, where_clause) = generics.split_for_impl();
+
+        let field_definitions = fields.iter().map(|Prop { ident, ty, .. }| {
+            quote! { pub #ident: #ty, }
+        });
+        let field_patterns = fields.iter().map(|Prop { pattern, .. }| {
+            quote! { #pattern, }
+        });
+
+        let builder = format_ident!("{}Builder", props);
+        let arguments = generic_arguments(generics);
+        // State of each prop in the builder, either `Unset` or `Set<T>`
+        let states: Vec<Ident> = (0..fields.len())
+            .map(|i| format_ident!("__P{}", i))
+            .collect();
+        let idents: Vec<&Ident> = fields.iter().map(|field| &field.ident).collect();
+        let unset = states.iter().map(|_| quote! { ibex::compose::Unset });
+
+        let mut builder_generics = generics.clone();
+        builder_generics
+            .params
+            .extend(states.iter().map(|state| -> GenericParam {
+                parse_quote! { #state }
+            }));
+
+        // Each setter is only implemented while its prop is unset, so a prop cannot be given twice
+        let setters = fields.iter().enumerate().map(|(i, Prop { ident, ty, .. })| {
+            let mut setter_generics = generics.clone();
+            setter_generics.params.extend(
+                states
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, _)| *j != i)
+                    .map(|(_, state)| -> GenericParam { parse_quote! { #state } }),
+            );
+            let (setter_impl_generics, _, setter_where_clause) = setter_generics.split_for_impl();
+            let with_state = |set: TokenStream| {
+                states.iter().enumerate().map(move |(j, state)| {
+                    if i == j {
+                        set.clone()
+                    } else {
+                        quote! { #state }
+                    }
+                })
+            };
+            let unset_states = with_state(quote! { ibex::compose::Unset });
+            let set_states = with_state(quote! { ibex::compose::Set<#ty> });
+            let others: Vec<_> = idents.iter().filter(|other| **other != ident).collect();
+            quote! {
+                impl #setter_impl_generics #builder <#(#arguments,)* #(#unset_states),*> #setter_where_clause {
+                    pub fn #ident(self, value: #ty) -> #builder <#(#arguments,)* #(#set_states),*> {
+                        #builder {
+                            #ident: ibex::compose::Set(value),
+                            #(#others: self.#others,)*
+                            __props: ::core::marker::PhantomData,
+                        }
+                    }
+                }
+            }
+        });
+
+        // Omitted props use their default value, so only they must implement `Default`
+        // Bounds are on the method, so a missing prop is reported as an unsatisfied bound
+        let mut build_bounds = Vec::new();
+        let mut build_fields = Vec::new();
+        for (
+            Prop {
+                ident, ty, default, ..
+            },
+            state,
+        ) in fields.iter().zip(&states)
+        {
+            match default {
+                Some(default) => {
+                    build_bounds.push(quote! { #state: ibex::compose::PropOr<#ty> });
+                    build_fields.push(quote! {
+                        #ident: ibex::compose::PropOr::or_else(self.#ident, || #default),
+                    });
+                }
+                None => {
+                    build_bounds.push(quote! { #state: ibex::compose::PropOrDefault<#ty> });
+                    build_fields.push(quote! {
+                        #ident: ibex::compose::PropOrDefault::or_default(self.#ident),
+                    });
+                }
+            }
+        }
+        let (builder_impl_generics, builder_type_generics, builder_where_clause) =
+            builder_generics.split_for_impl();
+
+        let doc = format!("Props for component [`{}`]", name);
+        let builder_doc = format!(
+            "Builder for [`{}`]. Any props which are not given use their default value",
+            props
+        );
+
+        tokens.extend(quote! {
+            #[doc = #doc]
+            #vis struct #props #generics #where_clause {
+                #(#field_definitions)*
+            }
+
+            impl #impl_generics #props #type_generics #where_clause {
+                pub fn builder() -> #builder <#(#arguments,)* #(#unset),*> {
+                    #builder {
+                        #(#idents: ibex::compose::Unset,)*
+                        __props: ::core::marker::PhantomData,
+                    }
+                }
+            }
+
+            #[doc = #builder_doc]
+            #vis struct #builder #builder_generics #builder_where_clause {
+                #(#idents: #states,)*
+                __props: ::core::marker::PhantomData<fn() -> #props #type_generics>,
+            }
+
+            #(#setters)*
+
+            impl #builder_impl_generics #builder #builder_type_generics #builder_where_clause {
+                pub fn build(self) -> #props #type_generics
+                where
+                    #(#build_bounds,)*
+                {
+                    #props { #(#build_fields)* }
+                }
+            }
+
+            #(#attrs)*
+            #vis #constness #asyncness #unsafety #abi fn #name #impl_generics (props: #props #type_generics) #output #where_clause {
+                let #props { #(#field_patterns)* } = props;
+                #(#statements)*
+            }
+        });
+    }
+}
+
+/// Arguments to use generics as parameters of a type. Eg. `<'a, T: Clone, const N: usize>` ->
+/// `'a`, `T`, `N`
+fn generic_arguments(generics: &Generics) -> Vec<TokenStream> {
+    generics
+        .params
+        .iter()
+        .map(|param| match param {
+            GenericParam::Lifetime(param) => param.lifetime.to_token_stream(),
+            GenericParam::Type(param) => param.ident.to_token_stream(),
+            GenericParam::Const(param) => param.ident.to_token_stream(),
+        })
+        .collect()
+}
+
+pub fn parse_component(function: ItemFn) -> syn::Result<Component> {
+    let mut fields = Vec::new();
+    let mut lifetimes = ElidedLifetimes {
+        lifetime: Lifetime::new(PROPS_LIFETIME, Span::call_site()),
+        is_used: false,
+    };
+    let mut errors = None::<syn::Error>;
+    let mut push_error = |error: syn::Error| match &mut errors {
+        Some(errors) => errors.combine(error),
+        None => errors = Some(error),
+    };
+
+    for input in &function.sig.inputs {
+        let input = match input {
+            FnArg::Typed(input) => input,
+            FnArg::Receiver(receiver) => {
+                push_error(syn::Error::new_spanned(
+                    receiver,
+                    "Component cannot take `self`",
+                ));
+                continue;
+            }
+        };
+        let Pat::Ident(pattern) = &*input.pat else {
+            push_error(syn::Error::new_spanned(
+                &input.pat,
+                "Component parameter must be a name, as it is used as the prop name",
+            ));
+            continue;
+        };
+
+        let mut default = None;
+        for attribute in &input.attrs {
+            if !attribute.path().is_ident("prop") {
+                push_error(syn::Error::new_spanned(
+                    attribute,
+                    "Only `#[prop(...)]` attributes are supported on component parameters",
+                ));
+                continue;
+            }
+            let result = attribute.parse_nested_meta(|meta| {
+                if meta.path.is_ident("default") {
+                    default = Some(meta.value()?.parse()?);
+                    Ok(())
+                } else {
+                    Err(meta.error("Unknown option. Expected `default`"))
+                }
+            });
+            if let Err(error) = result {
+                push_error(error);
+            }
+        }
+
+        // Name of the method which finishes the props builder
+        if pattern.ident == "build" {
+            push_error(syn::Error::new_spanned(
+                &pattern.ident,
+                "Component parameter cannot be named `build`",
+            ));
+            continue;
+        }
+
+        let mut ty = (*input.ty).clone();
+        lifetimes.visit_type_mut(&mut ty);
+        fields.push(Prop {
+            pattern: (*input.pat).clone(),
+            ident: pattern.ident.clone(),
+            ty,
+            default,
+        });
+    }
+
+    if let Some(errors) = errors {
+        return Err(errors);
+    }
+
+    let props = Ident::new(
+        &props_name(&function.sig.ident.to_string()),
+        function.sig.ident.span(),
+    );
+    let mut generics = function.sig.generics.clone();
+    let is_declared = generics
+        .lifetimes()
+        .any(|param| param.lifetime == lifetimes.lifetime);
+    if lifetimes.is_used && !is_declared {
+        let param = LifetimeParam::new(lifetimes.lifetime);
+        generics.params.insert(0, GenericParam::Lifetime(param));
+    }
+
+    Ok(Component {
+        function,
+        props,
+        generics,
+        fields,
+    })
+}
+
+/// Gives elided lifetimes in a parameter type a named lifetime, so the type can be used as a
+/// field of the props struct
+///
+/// Eg. `Option<&Post>` -> `Option<&'props Post>`.
+/// Lifetimes hidden in paths (Eg. `Cow<str>` instead of `Cow<'_, str>`) cannot be found
+struct ElidedLifetimes {
+    lifetime: Lifetime,
+    /// Whether any lifetime was replaced
+    is_used: bool,
+}
+
+impl VisitMut for ElidedLifetimes {
+    fn visit_type_reference_mut(&mut self, reference: &mut TypeReference) {
+        if reference.lifetime.is_none() {
+            reference.lifetime = Some(self.lifetime.clone());
+            self.is_used = true;
+        }
+        visit_mut::visit_type_reference_mut(self, reference);
+    }
+
+    fn visit_lifetime_mut(&mut self, lifetime: &mut Lifetime) {
+        if lifetime.ident == "_" {
+            *lifetime = self.lifetime.clone();
+            self.is_used = true;
+        }
+    }
+
+    // Elided lifetimes in function pointers and `Fn` traits are higher-ranked, so are left as-is
+    fn visit_type_bare_fn_mut(&mut self, _: &mut TypeBareFn) {}
+    fn visit_parenthesized_generic_arguments_mut(&mut self, _: &mut ParenthesizedGenericArguments) {
+    }
+}
+
+/// Name of the props struct for a component function
+///
+/// Eg. `post_card` -> `PostCardProps`
+pub fn props_name(function: &str) -> String {
+    let mut name = String::new();
+    for word in function.split('_') {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            name.extend(first.to_uppercase());
+            name.push_str(chars.as_str());
+        }
+    }
+    name + "Props"
+}
@@ -0,0 +1,212 @@
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::{parse_quote, Data, DeriveInput, Expr, Fields, LitStr, Member, Type};
+
+/// Implementation of `Component` for a data struct
+pub struct IntoView {
+    input: DeriveInput,
+    body: Body,
+}
+
+/// How a struct is rendered
+enum Body {
+    /// Call a function with a reference to the struct
+    Template(Expr),
+    /// Render each field as a term and description, in a definition list
+    List(Vec<Field>),
+}
+
+/// Field of a struct, rendered in a definition list
+struct Field {
+    member: Member,
+    ty: Type,
+    /// Term (`<dt>`) text
+    label: String,
+    /// Function to call with a reference to the field, instead of converting into a node
+    template: Option<Expr>,
+}
+
+/// Options given with `#[ibex(...)]`, on a struct or a field
+#[derive(Default)]
+struct Options {
+    /// `template = expr`
+    template: Option<Expr>,
+    /// `label = "..."`
+    label: Option<String>,
+    /// `skip`
+    skip: bool,
+}
+
+impl IntoView {
+    /// Body of `Component::render`, where `self` is the struct, or a reference to it if `by_ref`
+    fn render_body(&self, by_ref: bool) -> TokenStream {
+        let reference = (!by_ref).then(|| quote! { & });
+        match &self.body {
+            Body::Template(template) => quote! {
+                ibex::compose::View::from((#template)(#reference self))
+            },
+            Body::List(fields) => {
+                let fields = fields.iter().map(|field| {
+                    let Field {
+                        member,
+                        template,
+                        label,
+                        ..
+                    } = field;
+                    let value = match template {
+                        Some(template) => quote! { (#template)(&self.#member) },
+                        None if by_ref => quote! { &self.#member },
+                        None => quote! { self.#member },
+                    };
+                    quote! {
+                        dt { #label }
+                        dd { [#value] }
+                    }
+                });
+                quote! {
+                    ibex::view! {
+                        dl { #(#fields)* }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl ToTokens for IntoView {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let name = &self.input.ident;
+        let (impl_generics, type_generics, where_clause) = self.input.generics.split_for_impl();
+        let body = self.render_body(false);
+
+        // Render by reference, Eg. items of `&Vec<T>`
+        // Only usable if every field without a template can be rendered by reference
+        let mut ref_generics = self.input.generics.clone();
+        ref_generics.params.insert(0, parse_quote! { '__ibex });
+        let ref_where_clause = ref_generics.make_where_clause();
+        if let Body::List(fields) = &self.body {
+            for field in fields.iter().filter(|field| field.template.is_none()) {
+                let ty = &field.ty;
+                ref_where_clause.predicates.push(parse_quote! {
+                    ibex::compose::Node: ::std::convert::From<&'__ibex #ty>
+                });
+            }
+        }
+        let (ref_impl_generics, _, ref_where_clause) = ref_generics.split_for_impl();
+        let ref_body = self.render_body(true);
+
+        tokens.extend(quote! {
+            impl #impl_generics ibex::compose::Component for #name #type_generics #where_clause {
+                fn render(self) -> ibex::compose::View {
+                    #body
+                }
+            }
+            impl #ref_impl_generics ibex::compose::Component for &'__ibex #name #type_generics
+            #ref_where_clause
+            {
+                fn render(self) -> ibex::compose::View {
+                    #ref_body
+                }
+            }
+        });
+    }
+}
+
+pub fn derive_into_view(input: DeriveInput) -> syn::Result<IntoView> {
+    let options = parse_options(&input.attrs)?;
+    if let Some(label) = options.label {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            format!("`label = {:?}` can only be used on fields", label),
+        ));
+    }
+    if options.skip {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "`skip` can only be used on fields",
+        ));
+    }
+
+    if let Some(template) = options.template {
+        return Ok(IntoView {
+            input,
+            body: Body::Template(template),
+        });
+    }
+
+    let fields = match &input.data {
+        Data::Struct(data) => parse_fields(&data.fields)?,
+        _ => return Err(syn::Error::new_spanned(
+            &input.ident,
+            "`IntoView` can only be derived for structs, unless `#[ibex(template = ...)]` is given",
+        )),
+    };
+
+    Ok(IntoView {
+        input,
+        body: Body::List(fields),
+    })
+}
+
+/// Parse fields which are not skipped
+fn parse_fields(fields: &Fields) -> syn::Result<Vec<Field>> {
+    let mut parsed = Vec::new();
+    let mut errors = None::<syn::Error>;
+
+    for (i, field) in fields.iter().enumerate() {
+        let options = match parse_options(&field.attrs) {
+            Ok(options) => options,
+            Err(error) => {
+                match &mut errors {
+                    Some(errors) => errors.combine(error),
+                    None => errors = Some(error),
+                }
+                continue;
+            }
+        };
+        if options.skip {
+            continue;
+        }
+
+        let (member, default_label) = match &field.ident {
+            Some(ident) => (Member::Named(ident.clone()), ident.to_string()),
+            None => (Member::Unnamed(i.into()), i.to_string()),
+        };
+        parsed.push(Field {
+            member,
+            ty: field.ty.clone(),
+            label: options.label.unwrap_or(default_label),
+            template: options.template,
+        });
+    }
+
+    match errors {
+        Some(errors) => Err(errors),
+        None => Ok(parsed),
+    }
+}
+
+/// Parse all `#[ibex(...)]` attributes
+///
+/// Eg. `#[ibex(template = render_post)]`, `#[ibex(label = "Published", skip)]`
+fn parse_options(attributes: &[syn::Attribute]) -> syn::Result<Options> {
+    let mut options = Options::default();
+    for attribute in attributes {
+        if !attribute.path().is_ident("ibex") {
+            continue;
+        }
+        attribute.parse_nested_meta(|meta| {
+            if meta.path.is_ident("template") {
+                options.template = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("label") {
+                options.label = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("skip") {
+                options.skip = true;
+            } else {
+                return Err(meta.error("Unknown option. Expected `template`, `label`, or `skip`"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(options)
+}
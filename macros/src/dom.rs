@@ -1,49 +1,37 @@
-use proc_macro2::{Delimiter, TokenStream, TokenTree};
+use proc_macro2::{Delimiter, Span, TokenStream, TokenTree};
 use quote::{quote, ToTokens};
 
 pub struct Document {
-    lang: TokenTree,
+    /// Attributes of <html>, as an attribute group `[...]`
+    attributes: TokenTree,
     views: TokenStream,
 }
 
 impl ToTokens for Document {
     fn to_tokens(&self, tokens: &mut TokenStream) {
-        let Document { lang, views } = self;
+        let Document { attributes, views } = self;
         tokens.extend(quote! {
-            ::ibex::view! { #views }.document(#lang)
+            ::ibex::view! { HTML #attributes / #views }.document_no_lang()
         })
     }
 }
 
-pub fn parse_document(input: TokenStream) -> Document {
+pub fn parse_document(input: TokenStream) -> syn::Result<Document> {
     let mut tokens = input.into_iter();
 
-    let lang = match tokens.next() {
+    // Attributes are parsed by `view!`, as a `HTML` block
+    let attributes = match tokens.next() {
         Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Bracket => {
-            let mut tokens = group.stream().into_iter();
-
-            match tokens.next() {
-                Some(TokenTree::Ident(ident)) if ident == "lang" => (),
-                token => panic!("Expected attribute name `lang`, found {:#?}. Note: Currently, only `lang` attribute is supported for <html> tag", token),
-            }
-            match tokens.next() {
-                Some(TokenTree::Punct(punct)) if punct.to_string() == "=" => (),
-                token => panic!("Expected `=`, found {:#?}", token),
-            }
-
-            let value = tokens
-                .next()
-                .expect("Expected attribute value expression for `lang`");
-
-            if tokens.next().is_some() {
-                panic!("Expeceted end of attribute group");
-            }
-
-            value
+            TokenTree::Group(group)
+        }
+        token => {
+            return Err(syn::Error::new(
+                token.map_or(Span::call_site(), |token| token.span()),
+                "Expected attribute group for <html> tag. Eg. `[lang = \"en\"]`",
+            ))
         }
-        _ => panic!("Expected attribute group"),
     };
 
     let views = tokens.collect();
-    Document { lang, views }
+    Ok(Document { attributes, views })
 }
@@ -1,3 +1,5 @@
+mod component;
+mod derive;
 mod dom;
 mod view;
 
@@ -7,8 +9,31 @@ use quote::quote;
 
 #[proc_macro]
 pub fn view(input: pm1::TokenStream) -> pm1::TokenStream {
-    let view = view::parse_view(input.into());
-    quote! { #view }.into()
+    match view::parse_view(input.into()) {
+        Ok(mut view) => {
+            view.precompile();
+            quote! { #view }.into()
+        }
+        Err(error) => compile_errors(error, quote! { ibex::compose::View::new() }),
+    }
+}
+
+/// Same as `view!`, but evaluates to `Result<View, E>`
+///
+/// Errors of fallible component calls (`@name?[...]`) and expressions (`[expr?]`) are propagated
+/// out of the enclosing function
+#[proc_macro]
+pub fn try_view(input: pm1::TokenStream) -> pm1::TokenStream {
+    match view::parse_view(input.into()) {
+        Ok(mut view) => {
+            view.precompile();
+            quote! { ::core::result::Result::Ok(#view) }.into()
+        }
+        Err(error) => compile_errors(
+            error,
+            quote! { ::core::result::Result::Ok(ibex::compose::View::new()) },
+        ),
+    }
 }
 
 #[proc_macro]
@@ -18,6 +43,79 @@ pub fn routes(_input: pm1::TokenStream) -> pm1::TokenStream {
 
 #[proc_macro]
 pub fn document(input: pm1::TokenStream) -> pm1::TokenStream {
-    let document = dom::parse_document(input.into());
-    quote! { #document }.into()
+    match dom::parse_document(input.into()) {
+        Ok(document) => quote! { #document }.into(),
+        Err(error) => compile_errors(error, quote! { ::ibex::view! {}.document_no_lang() }),
+    }
+}
+
+/// Make a function into a component with named props
+///
+/// Parameters become fields of a generated props struct, named after the function.
+/// Eg. `fn post_card(title: String, compact: bool)` takes `PostCardProps`.
+///
+/// Props can be given by name in `view!`, and any omitted props use their default value.
+/// Eg. `@post_card[title = "Hello", compact]`.
+/// Props are given to a generated builder (Eg. `PostCardProps::builder()`), so only omitted props
+/// must implement `Default`. A prop whose type does not, such as `&Post`, is required
+///
+/// Props are named if any prop is given as `name = value`. Otherwise, end the props with `..` to
+/// use the props struct, such as for only flags or children. Eg. `@post_card[compact, ..]`,
+/// `@post_card[..] { ... }`
+///
+/// Use `#[prop(default = expr)]` on a parameter to change its default value. A parameter cannot be
+/// named `build`
+///
+/// Elided lifetimes in parameter types (Eg. `&str`, `Option<&Post>`, `Cow<'_, str>`) are given a
+/// lifetime parameter `'props`, which is added to the props struct and the function.
+/// Lifetimes hidden in a path (Eg. `Cow<str>`) must be written as `'_`
+#[proc_macro_attribute]
+pub fn component(args: pm1::TokenStream, input: pm1::TokenStream) -> pm1::TokenStream {
+    if let Some(token) = proc_macro2::TokenStream::from(args).into_iter().next() {
+        return syn::Error::new(token.span(), "`component` does not take any arguments")
+            .to_compile_error()
+            .into();
+    }
+    let function = syn::parse_macro_input!(input as syn::ItemFn);
+    match component::parse_component(function) {
+        Ok(component) => quote! { #component }.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+/// Implement `Component` for a struct, so it can be used in `view!`
+///
+/// By default, each field is rendered as a term and description in a definition list (`<dl>`).
+///
+/// `Component` is also implemented for a reference to the struct, such as to render items of
+/// `&Vec<T>`. It can only be used if every field can be rendered by reference (Eg. `String`,
+/// `&str`, a derived struct), or has a template
+///
+/// Options, with `#[ibex(...)]`:
+///  - On the struct: `template = function`, to render with a function which takes `&Self`
+///  - On a field: `template = function`, to render the field with a function which takes a
+///    reference to the field
+///  - On a field: `label = "..."`, to change the term text, instead of the field name
+///  - On a field: `skip`, to not render the field
+#[proc_macro_derive(IntoView, attributes(ibex))]
+pub fn derive_into_view(input: pm1::TokenStream) -> pm1::TokenStream {
+    let input = syn::parse_macro_input!(input as syn::DeriveInput);
+    match derive::derive_into_view(input) {
+        Ok(derive) => quote! { #derive }.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+/// Expand to `compile_error!` invocations for every error, followed by a placeholder value
+///
+/// Placeholder prevents extra type errors, where the macro output is used
+fn compile_errors(error: syn::Error, placeholder: proc_macro2::TokenStream) -> pm1::TokenStream {
+    let errors = error.to_compile_error();
+    quote! {
+        {
+            #errors
+            #placeholder
+        }
+    }
+    .into()
 }
@@ -1,29 +1,49 @@
-use proc_macro2::{Delimiter, TokenStream, TokenTree};
-use quote::{quote, ToTokens};
-use syn::Lit;
+use std::{fmt::Display, iter::Peekable, mem};
 
-use ibex_core::compose::Tag;
+use proc_macro2::{token_stream, Delimiter, Ident, Punct, Spacing, Span, TokenStream, TokenTree};
+use quote::{quote, quote_spanned, ToTokens};
+use syn::{spanned::Spanned, Lit};
 
-#[derive(Debug)]
+use ibex_core::compose::{self, DocumentTag, Namespace, Tag};
+use ibex_core::render::{escape_text, RenderOptions};
+
+use crate::component::props_name;
+
+#[derive(Debug, Default)]
 pub struct View(Vec<Node>);
 
 #[derive(Debug)]
 enum Node {
     HeadAppend(View),
+    BodyEndAppend(View),
     Element(Element),
     Literal(String),
+    Raw(String),
     Expression(TokenStream),
     Function(Function),
-    If(TokenStream, View, Option<View>),
+    /// Path of struct which implements `Component`, and its fields
+    Struct(TokenStream, TokenStream),
+    /// Each `if` or `else if` branch (condition and block), and `else` block
+    If(Vec<(TokenStream, View)>, Option<View>),
     For(TokenStream, View),
+    /// Scrutinee expression, and each arm (pattern with optional guard, and block)
+    Match(TokenStream, Vec<(TokenStream, View)>),
     With(TokenStream, View),
+    /// Context value, and block which is built with it
+    Context(TokenStream, View),
+    /// Attributes of <html>, <head>, or <body>, and children which are used in place
+    ///
+    /// Eg. `BODY [class="dark"] { ... }`
+    DocumentAttributes(DocumentTag, Vec<Attribute>, View),
+    /// Nodes without any expressions, and their html, rendered at compile time
+    Static(StaticHtml, View),
 }
 
 #[derive(Debug)]
 struct Element {
     tag: Tag,
     id: Option<TokenStream>,
-    class: Option<TokenStream>,
+    /// Includes classes, as a single `class` attribute
     attributes: Vec<Attribute>,
     children: View,
 }
@@ -31,10 +51,31 @@ struct Element {
 #[derive(Debug)]
 struct Function {
     name: TokenStream,
-    arguments: TokenStream,
+    /// `?` after name, if function returns a `Result`, which is propagated
+    fallible: Option<Punct>,
+    arguments: Arguments,
     children: Option<View>,
 }
 
+#[derive(Debug)]
+enum Arguments {
+    /// Arguments passed in order. Eg. `@name[a, b]`
+    Positional(TokenStream),
+    /// Props struct path, each named prop, and each named slot.
+    /// Eg. `@name[a = 1, b] { #c { ... } }`
+    ///
+    /// For components defined with `#[component]`
+    Named(TokenStream, Vec<NamedProp>, Vec<(Ident, View)>),
+}
+
+/// Named prop of a component
+#[derive(Debug)]
+struct NamedProp {
+    name: Ident,
+    /// `None` for a flag, which is `true`. Eg. `@card[compact]`
+    value: Option<TokenStream>,
+}
+
 #[derive(Debug)]
 enum Attribute {
     Pair {
@@ -45,6 +86,18 @@ enum Attribute {
         name: String,
         condition: TokenStream,
     },
+    /// Many classes, merged into one `class` attribute
+    Classes(Vec<Class>),
+    /// Each css property and value of `style` attribute. Eg. `[style={ width: pct }]`
+    Style(Vec<(String, TokenStream)>),
+}
+
+#[derive(Debug)]
+enum Class {
+    /// Class names, as a string expression. Eg. `.card`, `."card shadow"`, `.[name]`
+    Name(TokenStream),
+    /// Class names, and condition. Eg. `.{ "active": is_active }`
+    Conditional(TokenStream, TokenStream),
 }
 
 impl ToTokens for View {
@@ -67,59 +120,97 @@ impl ToTokens for Node {
             Node::HeadAppend(view) => {
                 tokens.extend(quote! { ibex::compose::Node::HeadAppend(#view.into()) })
             }
+            Node::BodyEndAppend(view) => {
+                tokens.extend(quote! { ibex::compose::Node::BodyEndAppend(#view.into()) })
+            }
             Node::Element(element) => {
                 tokens.extend(quote! { ibex::compose::Node::Element(#element) })
             }
             Node::Literal(string) => {
                 tokens.extend(quote! { ibex::compose::Node::Text(#string.to_string()) })
             }
+            Node::Raw(string) => {
+                tokens.extend(quote! { ibex::compose::Node::Raw(#string.to_string()) })
+            }
             Node::Expression(content) => {
                 let expr = quote! { #content };
                 tokens.extend(quote! { ibex::compose::Node::from(#expr) })
             }
             Node::Function(Function {
                 name,
+                fallible,
                 arguments,
                 children,
             }) => {
                 // Convert string of ident into ident
                 // let name = quote::format_ident!("{}", format!("{}", name));
-                let call = match children {
-                    Some(children) => {
+                let call = match (arguments, children) {
+                    (Arguments::Positional(arguments), Some(children)) => {
                         if arguments.is_empty() {
                             quote! { #name(#children) }
                         } else {
                             quote! { #name(#arguments, #children) }
                         }
                     }
-                    None => {
+                    (Arguments::Positional(arguments), None) => {
                         quote! { #name(#arguments) }
                     }
+                    // Props are given to the builder of the props struct, which uses the default
+                    // value of any missing props
+                    // Unnamed children are only given if not empty, so a component with only
+                    // named slots does not need a `children` prop
+                    (Arguments::Named(props, named_props, slots), children) => {
+                        let fields = named_props.iter().map(|NamedProp { name, value }| {
+                            let value = value.clone().unwrap_or_else(|| quote! { true });
+                            quote! { .#name(::core::convert::Into::into(#value)) }
+                        });
+                        let slots = slots.iter().map(|(name, view)| {
+                            quote! { .#name(::core::convert::Into::into(#view)) }
+                        });
+                        let children = children.iter().filter(|children| !children.0.is_empty());
+                        let children = children.map(|children| {
+                            quote! { .children(::core::convert::Into::into(#children)) }
+                        });
+                        // Missing props are reported at the component name
+                        let build = quote_spanned! { name.span()=> build };
+                        quote! {
+                            #name(#props::builder() #(#fields)* #(#slots)* #(#children)* .#build())
+                        }
+                    }
                 };
-                tokens.extend(quote! {ibex::compose::Node::Fragment(#call.into())})
+                tokens.extend(quote! {ibex::compose::Node::Fragment(#call #fallible.into())})
             }
+            Node::Struct(name, fields) => tokens.extend(quote! {
+                ibex::compose::Node::Fragment(
+                    ibex::compose::Component::render(#name { #fields })
+                )
+            }),
             // conditions cannot be wrapped in brackets, because `if-let` statements will break
             // this, however, makes errors look ugly with `else-if` chaining
-            Node::If(condition, then, otherwise) => match otherwise {
-                Some(otherwise) => tokens.extend(quote! {
-                    ibex::compose::Node::Fragment(
+            Node::If(branches, otherwise) => {
+                let mut chain = TokenStream::new();
+                for (i, (condition, then)) in branches.iter().enumerate() {
+                    if i > 0 {
+                        chain.extend(quote! { else });
+                    }
+                    chain.extend(quote! {
                         if #condition {
                             #then
-                        } else {
-                            #otherwise
                         }
-                    )
-                }),
-                None => tokens.extend(quote! {
+                    });
+                }
+                let otherwise = match otherwise {
+                    Some(otherwise) => quote! { #otherwise },
+                    None => quote! { ibex::compose::View::new() },
+                };
+                tokens.extend(quote! {
                     ibex::compose::Node::Fragment(
-                        if #condition {
-                            #then
-                        } else {
-                            view! {}
+                        #chain else {
+                            #otherwise
                         }
                     )
-                }),
-            },
+                })
+            }
             // must use `for` loop inside block, as opposed to `.map`, because `#source` is a
             // tokenstream, which does not separate tokens before and after `in` keyword
             Node::For(source, block) => tokens.extend(quote! {
@@ -132,12 +223,60 @@ impl ToTokens for Node {
                 })
             }),
 
+            Node::Match(scrutinee, arms) => {
+                let arms = arms.iter().map(|(pattern, view)| {
+                    quote! { #pattern => #view, }
+                });
+                tokens.extend(quote! {
+                    ibex::compose::Node::Fragment(
+                        match #scrutinee {
+                            #(#arms)*
+                        }
+                    )
+                })
+            }
+
             Node::With(scope, view) => tokens.extend(quote! {
                 {
                     #scope;
                     ibex::compose::Node::Fragment(#view)
                 }
             }),
+
+            Node::DocumentAttributes(tag, attributes, view) => {
+                let tag = quote::format_ident!("{}", format!("{:?}", tag));
+                let attribute_pushes = attributes.iter().map(Attribute::to_push);
+                tokens.extend(quote! {
+                    ibex::compose::Node::Fragment(ibex::compose::View(vec![
+                        ibex::compose::Node::DocumentAttributes(
+                            ibex::compose::DocumentTag::#tag,
+                            {
+                                let mut attributes = Vec::new();
+                                #(#attribute_pushes)*
+                                attributes
+                            },
+                        ),
+                        ibex::compose::Node::Fragment(#view),
+                    ]))
+                })
+            }
+
+            // Guard is used instead of a closure, so `?` can propagate from inside the block
+            Node::Context(value, view) => tokens.extend(quote! {
+                {
+                    let _guard = ibex::context::enter(#value);
+                    ibex::compose::Node::Fragment(#view)
+                }
+            }),
+
+            // Closure does not capture anything, so it can be a function pointer
+            Node::Static(StaticHtml { html, minified }, view) => tokens.extend(quote! {
+                ibex::compose::Node::Static(ibex::compose::StaticView {
+                    html: #html,
+                    minified: #minified,
+                    build: || #view,
+                })
+            }),
         }
     }
 }
@@ -147,13 +286,18 @@ impl ToTokens for Element {
         let Element {
             tag,
             id,
-            class,
             attributes,
             children,
         } = self;
 
-        // Convert string of ident into ident
-        let tag = quote::format_ident!("{}", format!("{:?}", tag));
+        let tag = match tag {
+            Tag::Custom(name) => quote! { ibex::compose::Tag::Custom(#name.to_string()) },
+            tag => {
+                // Convert string of ident into ident
+                let tag = quote::format_ident!("{}", format!("{:?}", tag));
+                quote! { ibex::compose::Tag::#tag }
+            }
+        };
 
         let mut children_tokens = TokenStream::new();
         children.to_tokens(&mut children_tokens);
@@ -168,37 +312,14 @@ impl ToTokens for Element {
                 });
             });
         }
-        if let Some(class) = class {
-            attribute_pushes.extend(quote! {
-                attributes.push(ibex::compose::Attribute {
-                    name: "class".to_string(),
-                    value: Some((#class).to_string()),
-                });
-            });
-        }
 
         for attribute in attributes {
-            match attribute {
-                Attribute::Pair { name, value } => attribute_pushes.extend(quote! {
-                    attributes.push(ibex::compose::Attribute {
-                        name: #name.to_string(),
-                        value: Some((#value).to_string()),
-                    });
-                }),
-                Attribute::Conditional { name, condition } => attribute_pushes.extend(quote! {
-                    if #condition {
-                        attributes.push(ibex::compose::Attribute {
-                            name: #name.to_string(),
-                            value: None,
-                        })
-                    };
-                }),
-            };
+            attribute_pushes.extend(attribute.to_push());
         }
 
         tokens.extend(quote! {
             ibex::compose::Element {
-                tag: ibex::compose::Tag::#tag,
+                tag: #tag,
                 attributes: {
                     let mut attributes = Vec::new();
                     #attribute_pushes;
@@ -210,473 +331,1572 @@ impl ToTokens for Element {
     }
 }
 
-pub fn parse_view(input: TokenStream) -> View {
-    let mut nodes = Vec::new();
+// ------------
+// Static nodes
+// ------------
+
+/// Where nodes will be rendered, which affects how static nodes are rendered
+#[derive(Clone, Copy, Debug)]
+enum Placement {
+    /// Inside an element of a known namespace
+    Known(Namespace),
+    /// Anywhere, such as the top level of a view, or children of a component
+    Unknown,
+    /// Inside a raw text element (`<script>` or `<style>`), where text is not escaped
+    RawText,
+    /// Top level of `HEAD`, where elements must not be rendered, so that duplicates can be removed
+    Head,
+}
 
-    let mut tokens = input.into_iter().peekable();
+impl Placement {
+    /// Placement of the children of an element with the given tag, in this placement
+    fn of_children(self, tag: &Tag) -> Self {
+        match self {
+            Placement::Known(namespace) => {
+                let namespace = namespace.of_element(tag);
+                if !namespace.is_foreign() && tag.is_raw_text() {
+                    Placement::RawText
+                } else {
+                    Placement::Known(namespace.of_children(tag))
+                }
+            }
+            // `<svg>` and `<math>` always begin a new namespace
+            Placement::Unknown if matches!(tag, Tag::Svg | Tag::Math) => {
+                let namespace = Namespace::Html.of_element(tag);
+                Placement::Known(namespace.of_children(tag))
+            }
+            Placement::Unknown if tag.is_raw_text() => Placement::RawText,
+            Placement::Unknown => Placement::Unknown,
+            Placement::RawText => Placement::RawText,
+            Placement::Head => Placement::Known(Namespace::Html).of_children(tag),
+        }
+    }
+}
 
-    while let Some(token) = tokens.next() {
-        match token {
-            TokenTree::Ident(ident) => {
-                let tag = ident.to_string();
+impl Attribute {
+    /// Push attribute onto `attributes`, if it is not a false condition
+    fn to_push(&self) -> TokenStream {
+        match self {
+            Attribute::Pair { name, value } => quote! {
+                attributes.push(ibex::compose::Attribute {
+                    name: #name.to_string(),
+                    value: Some((#value).to_string()),
+                });
+            },
+            Attribute::Conditional { name, condition } => quote! {
+                if #condition {
+                    attributes.push(ibex::compose::Attribute {
+                        name: #name.to_string(),
+                        value: None,
+                    })
+                };
+            },
+            // Omit attribute if all conditions are false
+            Attribute::Classes(classes) => {
+                let pushes = classes.iter().map(|class| match class {
+                    Class::Name(name) => quote! {
+                        classes.push(&(#name).to_string());
+                    },
+                    Class::Conditional(name, condition) => quote! {
+                        if #condition {
+                            classes.push(&(#name).to_string());
+                        }
+                    },
+                });
+                quote! {
+                    let mut classes = ibex::compose::ClassList::new();
+                    #(#pushes)*
+                    if !classes.is_empty() {
+                        attributes.push(ibex::compose::Attribute {
+                            name: "class".to_string(),
+                            value: Some(classes.to_string()),
+                        });
+                    }
+                }
+            }
+            // Omit attribute if all values are `None`
+            Attribute::Style(properties) => {
+                let properties = properties.iter().map(|(property, value)| {
+                    // Borrow value, like other attributes
+                    quote! { .with(#property, &(#value)) }
+                });
+                quote! {
+                    let style = ibex::compose::Style::new() #(#properties)*;
+                    if !style.is_empty() {
+                        attributes.push(ibex::compose::Attribute {
+                            name: "style".to_string(),
+                            value: Some(style.to_string()),
+                        });
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl View {
+    /// Render any nodes without expressions at compile time, replacing them with `Node::Static`
+    ///
+    /// Adjacent static nodes are rendered together
+    pub fn precompile(&mut self) {
+        self.precompile_in(Placement::Unknown);
+    }
+
+    fn precompile_in(&mut self, placement: Placement) {
+        if matches!(placement, Placement::Head) {
+            for node in &mut self.0 {
+                node.precompile_in(placement);
+            }
+            return;
+        }
+
+        let mut nodes = Vec::new();
+        let mut run = Vec::new();
+        for mut node in mem::take(&mut self.0) {
+            if node.to_compose().is_some() {
+                run.push(node);
+                continue;
+            }
+            push_static(mem::take(&mut run), &mut nodes, placement);
+            node.precompile_in(placement);
+            nodes.push(node);
+        }
+        push_static(run, &mut nodes, placement);
+        self.0 = nodes;
+    }
+}
 
-                let tag: Option<Tag> = if tag == "HEAD" {
-                    if !nodes.is_empty() {
-                        panic!("HEAD must be first element in group");
+/// Render static nodes, and push the result onto `nodes`
+///
+/// If nodes cannot be rendered together, each node is rendered separately, or its children are
+fn push_static(mut run: Vec<Node>, nodes: &mut Vec<Node>, placement: Placement) {
+    if run.is_empty() {
+        return;
+    }
+
+    let view = compose::View(run.iter().filter_map(Node::to_compose).collect());
+    if let Some(html) = render_static(&view, placement) {
+        nodes.push(Node::Static(html, View(run)));
+    } else if run.len() > 1 {
+        for node in run {
+            push_static(vec![node], nodes, placement);
+        }
+    } else if let Some(mut node) = run.pop() {
+        node.precompile_in(placement);
+        nodes.push(node);
+    }
+}
+
+/// Html of static nodes, rendered at compile time
+#[derive(Debug)]
+struct StaticHtml {
+    /// Rendered with default options
+    html: String,
+    /// Rendered with `RenderOptions::minified`, as children of an html element
+    ///
+    /// Only used inside html elements, so it does not depend on the namespace of the placement
+    minified: String,
+}
+
+impl StaticHtml {
+    fn new(view: &compose::View, html: String) -> Self {
+        Self {
+            html,
+            minified: view.render_orphan_in(Namespace::Html, &RenderOptions::minified()),
+        }
+    }
+}
+
+/// Render static nodes as html, if the output does not depend on where they are used
+fn render_static(view: &compose::View, placement: Placement) -> Option<StaticHtml> {
+    match placement {
+        Placement::Known(namespace) => {
+            let html = view.render_orphan_in(namespace, &RenderOptions::default());
+            Some(StaticHtml::new(view, html))
+        }
+        Placement::Unknown => {
+            // Text would be written differently inside a raw text element
+            let has_escaped_text = view.0.iter().any(
+                |node| matches!(node, compose::Node::Text(text) if escape_text(text) != *text),
+            );
+            if has_escaped_text {
+                return None;
+            }
+            let render_in = |namespace| view.render_orphan_in(namespace, &RenderOptions::default());
+            let html = render_in(Namespace::Html);
+            let is_same = [Namespace::Svg, Namespace::MathMl]
+                .into_iter()
+                .all(|namespace| render_in(namespace) == html);
+            is_same.then(|| StaticHtml::new(view, html))
+        }
+        Placement::RawText | Placement::Head => None,
+    }
+}
+
+impl Node {
+    /// Convert to a node which can be rendered at compile time, if the node has no expressions
+    ///
+    /// Returns `None` if the node would panic when rendered, so the panic happens at runtime
+    fn to_compose(&self) -> Option<compose::Node> {
+        match self {
+            Node::Element(element) => element.to_compose().map(compose::Node::Element),
+            Node::Literal(text) => Some(compose::Node::Text(text.clone())),
+            Node::Raw(html) => Some(compose::Node::Raw(html.clone())),
+            _ => None,
+        }
+    }
+
+    /// Render any static nodes inside this node
+    fn precompile_in(&mut self, placement: Placement) {
+        match self {
+            Node::HeadAppend(view) => view.precompile_in(Placement::Head),
+            Node::BodyEndAppend(view) => view.precompile_in(Placement::Known(Namespace::Html)),
+            Node::Element(element) => {
+                let placement = placement.of_children(&element.tag);
+                element.children.precompile_in(placement);
+            }
+            Node::Function(function) => {
+                if let Some(children) = &mut function.children {
+                    children.precompile_in(Placement::Unknown);
+                }
+                if let Arguments::Named(_, _, slots) = &mut function.arguments {
+                    for (_, view) in slots {
+                        view.precompile_in(Placement::Unknown);
                     }
-                    None
-                } else {
-                    let Ok(tag) = tag.as_str().try_into() else {
-                        panic!("Invalid tag name '{}'", tag);
-                    };
-                    Some(tag)
-                };
+                }
+            }
+            Node::If(branches, otherwise) => {
+                for (_, view) in branches {
+                    view.precompile_in(placement);
+                }
+                if let Some(otherwise) = otherwise {
+                    otherwise.precompile_in(placement);
+                }
+            }
+            Node::Match(_, arms) => {
+                for (_, view) in arms {
+                    view.precompile_in(placement);
+                }
+            }
+            Node::For(_, view)
+            | Node::With(_, view)
+            | Node::Context(_, view)
+            | Node::DocumentAttributes(_, _, view) => view.precompile_in(placement),
+            Node::Literal(_)
+            | Node::Raw(_)
+            | Node::Expression(_)
+            | Node::Struct(_, _)
+            | Node::Static(_, _) => (),
+        }
+    }
+}
 
-                // Id and class
-                let mut id = None;
-                let mut class = None;
-                if let Some(TokenTree::Punct(punct)) = tokens.peek() {
-                    if punct.to_string() == "#" {
-                        tokens.next();
-                        let Some(token) = tokens.next() else {
-                            panic!("Expected id name");
-                        };
-                        match token {
-                            TokenTree::Literal(literal) => {
-                                id = Some(quote! { #literal });
-                            }
-                            TokenTree::Group(group) if group.delimiter() == Delimiter::Bracket => {
-                                id = Some(group.stream());
-                            }
-                            _ => panic!("Id name must be literal or group"),
-                        }
+impl Element {
+    fn to_compose(&self) -> Option<compose::Element> {
+        let children = compose::View(
+            self.children
+                .0
+                .iter()
+                .map(Node::to_compose)
+                .collect::<Option<_>>()?,
+        );
+        if !Tag::is_valid_name(self.tag.as_str()) || (self.tag.is_void() && !children.0.is_empty())
+        {
+            return None;
+        }
+
+        let mut attributes = Vec::new();
+        if let Some(id) = &self.id {
+            attributes.push(compose::Attribute {
+                name: "id".to_string(),
+                value: Some(literal_string(id)?),
+            });
+        }
+        for attribute in &self.attributes {
+            match attribute {
+                Attribute::Pair { name, value } => attributes.push(compose::Attribute {
+                    name: name.clone(),
+                    value: Some(literal_string(value)?),
+                }),
+                Attribute::Conditional { name, condition } => {
+                    if literal_bool(condition)? {
+                        attributes.push(compose::Attribute {
+                            name: name.clone(),
+                            value: None,
+                        });
                     }
                 }
-                if let Some(TokenTree::Punct(punct)) = tokens.peek() {
-                    if punct.to_string() == "." {
-                        tokens.next();
-                        let Some(token) = tokens.next() else {
-                            panic!("Expected class name");
-                        };
-                        match token {
-                            TokenTree::Literal(literal) => {
-                                class = Some(quote! { #literal });
-                            }
-                            TokenTree::Group(group) if group.delimiter() == Delimiter::Bracket => {
-                                class = Some(group.stream());
+                Attribute::Classes(classes) => {
+                    let mut class_list = compose::ClassList::new();
+                    for class in classes {
+                        match class {
+                            Class::Name(name) => class_list.push(&literal_string(name)?),
+                            Class::Conditional(name, condition) => {
+                                if literal_bool(condition)? {
+                                    class_list.push(&literal_string(name)?);
+                                }
                             }
-                            _ => panic!("Class name must be literal or group"),
                         }
                     }
+                    if !class_list.is_empty() {
+                        attributes.push(compose::Attribute {
+                            name: "class".to_string(),
+                            value: Some(class_list.to_string()),
+                        });
+                    }
                 }
+                Attribute::Style(_) => return None,
+            }
+        }
+        if !attributes
+            .iter()
+            .all(|attribute| compose::Attribute::is_valid_name(&attribute.name))
+        {
+            return None;
+        }
 
-                // Disallow id after class
-                if let Some(TokenTree::Punct(punct)) = tokens.peek() {
-                    if punct.to_string() == "#" {
-                        panic!("Id must not come after class name");
-                    }
+        Some(compose::Element {
+            tag: self.tag.clone(),
+            attributes,
+            children,
+        })
+    }
+}
+
+/// Get the value of a string literal, if tokens are only a string literal
+fn literal_string(tokens: &TokenStream) -> Option<String> {
+    syn::parse2::<syn::LitStr>(tokens.clone())
+        .ok()
+        .map(|literal| literal.value())
+}
+
+/// Get the value of a boolean literal, if tokens are only `true` or `false`
+fn literal_bool(tokens: &TokenStream) -> Option<bool> {
+    syn::parse2::<syn::LitBool>(tokens.clone())
+        .ok()
+        .map(|literal| literal.value)
+}
+
+/// Token iterator for a view or group
+type Tokens = Peekable<token_stream::IntoIter>;
+
+/// Collection of errors, so that many errors can be reported at once
+#[derive(Default)]
+pub struct Errors(Option<syn::Error>);
+
+impl Errors {
+    /// Add an error to the collection
+    pub fn push(&mut self, error: syn::Error) {
+        match &mut self.0 {
+            Some(errors) => errors.combine(error),
+            None => self.0 = Some(error),
+        }
+    }
+
+    /// Add error to the collection if result is `Err`, otherwise return `Ok` value
+    pub fn take<T>(&mut self, result: syn::Result<T>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(error) => {
+                self.push(error);
+                None
+            }
+        }
+    }
+
+    /// Return value if no errors were added, otherwise return all errors
+    pub fn finish<T>(self, value: T) -> syn::Result<T> {
+        match self.0 {
+            Some(errors) => Err(errors),
+            None => Ok(value),
+        }
+    }
+}
+
+/// Shorthand to create an error with a span
+fn error<T>(span: Span, message: impl Display) -> syn::Result<T> {
+    Err(syn::Error::new(span, message))
+}
+
+/// Returns `true` if token is a punctuation character
+fn is_punct(token: Option<&TokenTree>, ch: char) -> bool {
+    matches!(token, Some(TokenTree::Punct(punct)) if punct.as_char() == ch)
+}
+
+pub fn parse_view(input: TokenStream) -> syn::Result<View> {
+    let (view, slots) = parse_view_with_slots(input)?;
+
+    let mut errors = Errors::default();
+    for (name, _) in slots {
+        errors.push(syn::Error::new(
+            name.span(),
+            format!(
+                "Named slot `#{}` can only be used in the children of a component",
+                name
+            ),
+        ));
+    }
+    errors.finish(view)
+}
+
+/// Parse a view, which may contain named slots
+///
+/// Eg. `#sidebar { ... }`
+fn parse_view_with_slots(input: TokenStream) -> syn::Result<(View, Vec<(Ident, View)>)> {
+    let mut nodes = Vec::new();
+    let mut slots = Vec::new();
+    let mut errors = Errors::default();
+
+    let mut tokens = input.into_iter().peekable();
+
+    while let Some(token) = tokens.next() {
+        // Keep parsing after an error, to report as many errors as possible
+        if matches!(&token, TokenTree::Punct(punct) if punct.as_char() == '#') {
+            if let Some(slot) = errors.take(parse_slot(token, &mut tokens)) {
+                slots.push(slot);
+            }
+        } else if let Some(node) = errors.take(parse_node(token, &mut tokens)) {
+            nodes.push(node);
+        }
+    }
+
+    errors.finish((View(nodes), slots))
+}
+
+/// Parse a named slot, beginning with `#`
+fn parse_slot(hash: TokenTree, tokens: &mut Tokens) -> syn::Result<(Ident, View)> {
+    let name = match tokens.next() {
+        Some(TokenTree::Ident(name)) => name,
+        Some(token) => return error(token.span(), "Expected slot name after `#`"),
+        None => return error(hash.span(), "Expected slot name after `#`"),
+    };
+    match tokens.next() {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => {
+            Ok((name, parse_view(group.stream())?))
+        }
+        Some(token) => error(
+            token.span(),
+            format!("Expected block `{{...}}` after slot name `{}`", name),
+        ),
+        None => error(
+            name.span(),
+            format!("Expected block `{{...}}` after slot name `{}`", name),
+        ),
+    }
+}
+
+/// Parse a single node, beginning with the given token
+fn parse_node(token: TokenTree, tokens: &mut Tokens) -> syn::Result<Node> {
+    match token {
+        TokenTree::Ident(ident) => parse_element(ident, tokens),
+
+        TokenTree::Literal(literal) => match Lit::new(literal) {
+            Lit::Str(string) => Ok(Node::Literal(string.value())),
+            literal => error(literal.span(), "Only string literals are allowed"),
+        },
+
+        TokenTree::Punct(punct) if punct.as_char() == '@' => parse_function(punct, tokens),
+
+        TokenTree::Group(group) if group.delimiter() == Delimiter::Bracket => {
+            let mut stream = group.stream().into_iter().peekable();
+
+            // Special `if`, `for`, and `where` statements
+            if is_punct(stream.peek(), ':') {
+                stream.next();
+                return parse_statement(group.span(), stream);
+            }
+
+            Ok(Node::Expression(stream.collect()))
+        }
+
+        // Tilde for whitespace
+        TokenTree::Punct(punct) if punct.as_char() == '~' => {
+            // Double tilde for linebreak
+            if is_punct(tokens.peek(), '~') {
+                tokens.next();
+                return Ok(Node::Literal("\n".to_string()));
+            }
+            Ok(Node::Literal(" ".to_string()))
+        }
+
+        // Html entity
+        TokenTree::Punct(punct) if punct.as_char() == '&' => {
+            let code = match tokens.next() {
+                Some(TokenTree::Ident(ident)) => ident.to_string(),
+                Some(TokenTree::Literal(literal)) if literal.to_string().parse::<i32>().is_ok() => {
+                    format!("#{}", literal)
+                }
+                Some(token) => {
+                    return error(
+                        token.span(),
+                        "Expected html entity code, as ident or codepoint integer literal",
+                    )
+                }
+                None => return error(punct.span(), "Expected html entity code after `&`"),
+            };
+            // optional semicolon after code
+            if is_punct(tokens.peek(), ';') {
+                tokens.next();
+            }
+            // Entity is already valid html, so must not be escaped
+            Ok(Node::Raw(format!("&{};", code)))
+        }
+
+        TokenTree::Group(group) if group.delimiter() == Delimiter::Brace => error(
+            group.span(),
+            "Unexpected braces. Use `[...]` for expressions, or an element tag before braces",
+        ),
+
+        token => error(token.span(), format!("Unexpected token `{}`", token)),
+    }
+}
+
+/// Parse an element (or `HEAD`, `HTML`, `BODY`, or `BODY_END` block), beginning with the tag name
+fn parse_element(ident: Ident, tokens: &mut Tokens) -> syn::Result<Node> {
+    let mut errors = Errors::default();
+
+    let (tag, is_custom) = parse_tag_name(&ident, tokens)?;
+
+    let document_tag = match tag.as_str() {
+        "HTML" => Some(DocumentTag::Html),
+        "HEAD" => Some(DocumentTag::Head),
+        "BODY" => Some(DocumentTag::Body),
+        _ => None,
+    };
+
+    let is_body_end = tag == "BODY_END";
+
+    let tag: Option<Tag> = if document_tag.is_some() || is_body_end {
+        None
+    } else if is_custom {
+        // Some known tags are hyphenated, Eg. `annotation-xml`
+        Some(Tag::from_name(tag))
+    } else {
+        match tag.as_str().try_into() {
+            Ok(tag) => Some(tag),
+            Err(()) => {
+                errors.push(syn::Error::new(
+                    ident.span(),
+                    format!(
+                        "Invalid tag name `{}`. Note: Custom element names must contain a hyphen",
+                        tag
+                    ),
+                ));
+                None
+            }
+        }
+    };
+
+    // Id and classes
+    let mut id = None;
+    let mut classes = Vec::new();
+    if let Some(TokenTree::Punct(punct)) = tokens.peek() {
+        if punct.as_char() == '#' {
+            let punct = punct.clone();
+            tokens.next();
+            id = errors.take(parse_selector_value(&punct, tokens, "Id"));
+        }
+    }
+    while let Some(TokenTree::Punct(punct)) = tokens.peek() {
+        if punct.as_char() != '.' {
+            break;
+        }
+        let punct = punct.clone();
+        tokens.next();
+        if let Some(class) = errors.take(parse_class(&punct, tokens)) {
+            classes.extend(class);
+        }
+    }
+
+    // Disallow id after class
+    if let Some(TokenTree::Punct(punct)) = tokens.peek() {
+        if punct.as_char() == '#' {
+            errors.push(syn::Error::new(
+                punct.span(),
+                "Id must not come after class name",
+            ));
+            tokens.next();
+            errors.take(parse_selector_value(&ident, tokens, "Id"));
+        }
+    }
+
+    // Attributes
+    let mut attributes = Vec::new();
+    if let Some(TokenTree::Group(group)) = tokens.peek() {
+        if group.delimiter() == Delimiter::Bracket {
+            let stream = group.stream();
+            tokens.next();
+            attributes = errors.take(parse_attributes(stream)).unwrap_or_default();
+        }
+    }
+    let attributes = merge_classes(classes, attributes);
+
+    let children = match tokens.next() {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => {
+            if let Some(tag) = &tag {
+                if tag.is_void() {
+                    errors.push(syn::Error::new(
+                        group.span(),
+                        format!(
+                            "Void tag <{}> cannot contain children. Use `/` to close.",
+                            tag
+                        ),
+                    ));
                 }
+            }
+            errors.take(parse_view(group.stream()))
+        }
 
-                // Attributes
-                let mut attributes = Vec::new();
-                if let Some(TokenTree::Group(group)) = tokens.peek() {
-                    if group.delimiter() == Delimiter::Bracket {
-                        let mut group = group.stream().into_iter().peekable();
-                        tokens.next();
+        Some(TokenTree::Punct(punct)) if punct.as_char() == '/' => Some(View(Vec::new())),
+
+        Some(token) => {
+            errors.push(syn::Error::new(
+                token.span(),
+                format!(
+                    "Expected braces `{{...}}` or single slash `/` after <{}> tag",
+                    ident
+                ),
+            ));
+            None
+        }
+        None => {
+            errors.push(syn::Error::new(
+                ident.span(),
+                format!(
+                    "Unexpected end. Missing braces `{{...}}` or single slash `/` after <{}> tag",
+                    ident
+                ),
+            ));
+            None
+        }
+    };
 
-                        loop {
-                            let Some(name) = group.next() else {
-                                panic!("Missing attribute name");
-                            };
-                            let name = name.to_string();
+    if is_body_end && (id.is_some() || !attributes.is_empty()) {
+        errors.push(syn::Error::new(
+            ident.span(),
+            "BODY_END cannot have an id, class, or attributes. Use `BODY [...]` instead",
+        ));
+    }
 
-                            let mut value = TokenStream::new();
-                            let mut is_conditional = false;
+    let children = errors.finish(children)?.unwrap_or_default();
 
-                            if matches!(group.peek(), Some(TokenTree::Punct(punct)) if punct.to_string() == "!")
-                            {
-                                // always `true` no-value attribute
-                                // same as `name?=true` (conditional with literal)
-                                group.next();
-                                attributes.push(Attribute::Conditional {
-                                    name,
-                                    condition: quote!(true),
-                                });
-                            } else {
-                                if matches!(group.peek(), Some(TokenTree::Punct(punct)) if punct.to_string() == "?")
-                                {
-                                    is_conditional = true;
-                                    group.next();
-                                }
+    if is_body_end {
+        return Ok(Node::BodyEndAppend(children));
+    }
 
-                                match group.next() {
-                                    Some(TokenTree::Punct(punct)) if punct.to_string() == "=" => {}
-                                    Some(TokenTree::Punct(punct)) => {
-                                        panic!(
-                                            "Unexpected punctuation token `{}`. Expected value",
-                                            punct
-                                        );
-                                    }
-                                    _ => {
-                                        panic!("Unexpected end of attributes. Expected value");
-                                    }
-                                }
+    let Some(document_tag) = document_tag else {
+        let tag = tag.expect("invalid tag should be an error");
+        return Ok(Node::Element(Element {
+            tag,
+            id,
+            attributes,
+            children,
+        }));
+    };
+
+    // Id is an attribute of the document element
+    let id = id.map(|value| Attribute::Pair {
+        name: "id".to_string(),
+        value,
+    });
+    let attributes: Vec<_> = id.into_iter().chain(attributes).collect();
+
+    // Children of `HEAD` are added to <head>, and other children are used in place
+    Ok(match document_tag {
+        DocumentTag::Head if attributes.is_empty() => Node::HeadAppend(children),
+        DocumentTag::Head => Node::DocumentAttributes(
+            document_tag,
+            attributes,
+            View(vec![Node::HeadAppend(children)]),
+        ),
+        _ => Node::DocumentAttributes(document_tag, attributes, children),
+    })
+}
 
-                                loop {
-                                    let next = group.peek();
-                                    match next {
-                                        Some(TokenTree::Punct(punct))
-                                            if punct.to_string() == "," =>
-                                        {
-                                            group.next();
-                                            break;
-                                        }
-                                        None => break,
-                                        Some(token) => {
-                                            value.extend(token.to_token_stream());
-                                            group.next();
-                                        }
-                                    }
-                                }
+/// Parse a (possibly hyphenated) tag name
+///
+/// Returns `true` if name is hyphenated, such as a custom element. Eg. `my-widget`
+fn parse_tag_name(ident: &Ident, tokens: &mut Tokens) -> syn::Result<(String, bool)> {
+    let mut tag = ident.to_string();
+    let mut is_custom = false;
+
+    while is_punct(tokens.peek(), '-') {
+        tokens.next();
+        match tokens.next() {
+            Some(TokenTree::Ident(ident)) => tag += &format!("-{}", ident),
+            Some(TokenTree::Literal(literal)) if literal.to_string().parse::<u32>().is_ok() => {
+                tag += &format!("-{}", literal)
+            }
+            token => {
+                return error(
+                    token.map_or(ident.span(), |token| token.span()),
+                    format!("Expected ident after `-` in custom tag name `{}-`", tag),
+                )
+            }
+        }
+        is_custom = true;
+    }
 
-                                attributes.push(if is_conditional {
-                                    Attribute::Conditional {
-                                        name,
-                                        condition: value,
-                                    }
-                                } else {
-                                    Attribute::Pair { name, value }
-                                });
-                            }
+    Ok((tag, is_custom))
+}
 
-                            if let Some(TokenTree::Punct(punct)) = group.peek() {
-                                if punct.to_string() == "," {
-                                    group.next();
-                                }
-                            }
+/// Parse a class, after `.`
+///
+/// Eg. `.card`, `.btn-primary`, `."card shadow"`, `.[name]`, `.{ "active": is_active }`
+fn parse_class(dot: &Punct, tokens: &mut Tokens) -> syn::Result<Vec<Class>> {
+    match tokens.peek() {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => {
+            let (stream, span) = (group.stream(), group.span());
+            tokens.next();
+            parse_conditional_classes(stream, span)
+        }
+        _ => Ok(vec![Class::Name(parse_class_name(dot, tokens)?)]),
+    }
+}
 
-                            if group.peek().is_none() {
-                                break;
-                            }
-                        }
-                    }
+/// Parse class names, as a string expression
+fn parse_class_name(previous: &impl Spanned, tokens: &mut Tokens) -> syn::Result<TokenStream> {
+    match tokens.next() {
+        Some(TokenTree::Literal(literal)) => Ok(quote! { #literal }),
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Bracket => {
+            Ok(group.stream())
+        }
+        // Same as a (possibly hyphenated) tag name
+        Some(TokenTree::Ident(ident)) => {
+            let (name, _) = parse_tag_name(&ident, tokens)?;
+            let name = syn::LitStr::new(&name, ident.span());
+            Ok(quote! { #name })
+        }
+        Some(token) => error(
+            token.span(),
+            "Class name must be a name, literal, group `[...]`, or conditional group `{...}`",
+        ),
+        None => error(previous.span(), "Expected class name"),
+    }
+}
+
+/// Parse conditional classes, inside braces
+///
+/// Eg. `{ "active": is_active, hidden: !visible }`
+fn parse_conditional_classes(stream: TokenStream, span: Span) -> syn::Result<Vec<Class>> {
+    let mut tokens = stream.into_iter().peekable();
+    let mut classes = Vec::new();
+
+    while let Some(token) = tokens.peek() {
+        let span = token.span();
+        let name = parse_class_name(&span, &mut tokens)?;
+        match tokens.next() {
+            Some(TokenTree::Punct(punct)) if punct.as_char() == ':' => (),
+            token => {
+                return error(
+                    token.map_or(span, |token| token.span()),
+                    "Expected `:` and condition after class name",
+                )
+            }
+        }
+        let mut condition = TokenStream::new();
+        while tokens.peek().is_some() && !is_punct(tokens.peek(), ',') {
+            condition.extend(tokens.next());
+        }
+        if condition.is_empty() {
+            return error(span, "Expected condition after class name");
+        }
+        tokens.next();
+        classes.push(Class::Conditional(name, condition));
+    }
+
+    if classes.is_empty() {
+        return error(
+            span,
+            "Expected conditional classes. Eg. `{ \"active\": is_active }`",
+        );
+    }
+    Ok(classes)
+}
+
+/// Merge classes and any `class` attributes into one attribute, in the position of the first
+///
+/// A single class is an ordinary attribute
+fn merge_classes(classes: Vec<Class>, mut attributes: Vec<Attribute>) -> Vec<Attribute> {
+    let is_class = |attribute: &Attribute| matches!(attribute, Attribute::Pair { name, .. } if name == "class");
+    let class_attributes = attributes
+        .iter()
+        .filter(|attribute| is_class(attribute))
+        .count();
+
+    let index = if classes.is_empty() {
+        match attributes.iter().position(is_class) {
+            Some(index) if class_attributes > 1 => index,
+            _ => return attributes,
+        }
+    } else {
+        0
+    };
+
+    let mut merged = classes;
+    attributes.retain(|attribute| match attribute {
+        Attribute::Pair { name, value } if name == "class" => {
+            merged.push(Class::Name(value.clone()));
+            false
+        }
+        _ => true,
+    });
+
+    let attribute = match merged.as_slice() {
+        [Class::Name(value)] => Attribute::Pair {
+            name: "class".to_string(),
+            value: value.clone(),
+        },
+        _ => Attribute::Classes(merged),
+    };
+    attributes.insert(index, attribute);
+    attributes
+}
+
+/// Parse value of id, after `#`
+fn parse_selector_value(
+    previous: &impl Spanned,
+    tokens: &mut Tokens,
+    kind: &str,
+) -> syn::Result<TokenStream> {
+    match tokens.next() {
+        Some(TokenTree::Literal(literal)) => Ok(quote! { #literal }),
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Bracket => {
+            Ok(group.stream())
+        }
+        Some(token) => error(
+            token.span(),
+            format!("{} name must be literal or group `[...]`", kind),
+        ),
+        None => error(
+            previous.span(),
+            format!("Expected {} name", kind.to_lowercase()),
+        ),
+    }
+}
+
+/// Parse attributes inside brackets
+///
+/// Eg. `[name=value, name?=condition, name!]`
+fn parse_attributes(stream: TokenStream) -> syn::Result<Vec<Attribute>> {
+    let mut attributes = Vec::new();
+    let mut errors = Errors::default();
+
+    let mut group = stream.into_iter().peekable();
+    while group.peek().is_some() {
+        if let Some(attribute) = errors.take(parse_attribute(&mut group)) {
+            attributes.push(attribute);
+        } else {
+            // Skip to next attribute
+            for token in group.by_ref() {
+                if is_punct(Some(&token), ',') {
+                    break;
                 }
+            }
+        }
+    }
 
-                let Some(next) = tokens.next() else {
-                    panic!("Unexpected end. Missing braces or single slash.");
-                };
+    errors.finish(attributes)
+}
 
-                let children = match next {
-                    TokenTree::Group(group) => {
-                        if group.delimiter() != Delimiter::Brace {
-                            panic!("Group must have braces: {{...}}");
-                        }
+/// Parse a single attribute, and the comma following it
+fn parse_attribute(group: &mut Tokens) -> syn::Result<Attribute> {
+    let (name, span) = parse_attribute_name(group)?;
+    if !compose::Attribute::is_valid_name(&name) {
+        return error(span, format!("Invalid attribute name `{}`", name));
+    }
 
-                        if let Some(tag) = tag {
-                            if tag.is_void() {
-                                panic!("Void tag <{}> cannot contain children. Use `/` to close.", tag);
-                            }
-                        }
+    let attribute = if is_punct(group.peek(), '!') {
+        // always `true` no-value attribute
+        // same as `name?=true` (conditional with literal)
+        group.next();
+        Attribute::Conditional {
+            name,
+            condition: quote!(true),
+        }
+    } else {
+        let mut is_conditional = false;
+        if is_punct(group.peek(), '?') {
+            is_conditional = true;
+            group.next();
+        }
 
-                        parse_view(group.stream())
+        let equals_span = match group.next() {
+            Some(TokenTree::Punct(punct)) if punct.as_char() == '=' => punct.span(),
+            Some(token) => {
+                return error(
+                    token.span(),
+                    format!(
+                        "Unexpected token `{}` after attribute name `{}`. \
+                         Expected `=`, `?=`, or `!`",
+                        token, name
+                    ),
+                )
+            }
+            None => {
+                return error(
+                    span,
+                    format!(
+                        "Unexpected end of attributes. Expected value for attribute `{}`",
+                        name
+                    ),
+                )
+            }
+        };
+
+        let mut value = TokenStream::new();
+        while group.peek().is_some() && !is_punct(group.peek(), ',') {
+            value.extend(group.next());
+        }
+        if value.is_empty() {
+            return error(
+                equals_span,
+                format!("Expected value for attribute `{}`", name),
+            );
+        }
+
+        if is_conditional {
+            Attribute::Conditional {
+                name,
+                condition: value,
+            }
+        } else if name == "style" && is_brace_group(&value) {
+            Attribute::Style(parse_style(value)?)
+        } else {
+            Attribute::Pair { name, value }
+        }
+    };
+
+    match group.next() {
+        None => (),
+        Some(TokenTree::Punct(punct)) if punct.as_char() == ',' => (),
+        Some(token) => {
+            return error(
+                token.span(),
+                format!("Expected `,` after attribute, found `{}`", token),
+            )
+        }
+    }
+
+    Ok(attribute)
+}
+
+/// Whether tokens are only a group `{...}`
+fn is_brace_group(tokens: &TokenStream) -> bool {
+    let mut tokens = tokens.clone().into_iter();
+    matches!(
+        (tokens.next(), tokens.next()),
+        (Some(TokenTree::Group(group)), None) if group.delimiter() == Delimiter::Brace
+    )
+}
+
+/// Parse css properties and values of `style` attribute, inside braces
+///
+/// Eg. `{ width: pct, background-color: color, --accent: accent }`
+fn parse_style(value: TokenStream) -> syn::Result<Vec<(String, TokenStream)>> {
+    let Some(TokenTree::Group(group)) = value.into_iter().next() else {
+        unreachable!("should be a group");
+    };
+    let mut tokens = group.stream().into_iter().peekable();
+    let mut properties = Vec::new();
+
+    while let Some(first) = tokens.peek() {
+        let span = first.span();
+
+        // Property name, until `:`
+        let mut property = String::new();
+        loop {
+            match tokens.next() {
+                Some(TokenTree::Punct(punct)) if punct.as_char() == ':' => break,
+                Some(TokenTree::Punct(punct)) if punct.as_char() == '-' => property.push('-'),
+                Some(TokenTree::Ident(ident)) => property += &ident.to_string(),
+                Some(TokenTree::Literal(literal)) => {
+                    match syn::parse2::<syn::LitStr>(TokenTree::Literal(literal.clone()).into()) {
+                        Ok(string) => property += &string.value(),
+                        Err(_) => property += &literal.to_string(),
                     }
+                }
+                Some(token) => {
+                    return error(
+                        token.span(),
+                        format!("Unexpected token `{}` in css property name", token),
+                    )
+                }
+                None => {
+                    return error(
+                        span,
+                        "Expected `:` and value after css property name. Eg. `{ width: value }`",
+                    )
+                }
+            }
+        }
 
-                    TokenTree::Punct(punct) if punct.to_string() == "/" => View(Vec::new()),
+        if property.is_empty() {
+            return error(span, "Expected css property name before `:`");
+        }
 
-                    _ => panic!("Expected group or single slash"),
-                };
+        let mut value = TokenStream::new();
+        while tokens.peek().is_some() && !is_punct(tokens.peek(), ',') {
+            value.extend(tokens.next());
+        }
+        if value.is_empty() {
+            return error(
+                span,
+                format!("Expected value for css property `{}`", property),
+            );
+        }
+        tokens.next();
+        properties.push((property, value));
+    }
 
-                match tag {
-                    None => nodes.push(Node::HeadAppend(children)),
-                    Some(tag) => nodes.push(Node::Element(Element {
-                        tag,
-                        id,
-                        class,
-                        attributes,
-                        children,
-                    })),
+    Ok(properties)
+}
+
+/// Parse a (possibly multi-token) attribute name
+///
+/// Name parts are separated with `-`, `:`, or `.`, and may begin with `@` or `:`.
+/// Eg. `aria-label`, `xml:lang`, `@click`, `x-on:click.prevent`
+///
+/// Otherwise, any name can be given as a string literal. Eg. `"x-on:click.prevent"`
+///
+/// Returns name and span of first token
+fn parse_attribute_name(group: &mut Tokens) -> syn::Result<(String, Span)> {
+    let mut name = String::new();
+
+    let span = match group.next() {
+        Some(TokenTree::Literal(literal)) => {
+            let span = literal.span();
+            return match Lit::new(literal) {
+                Lit::Str(string) => Ok((string.value(), span)),
+                _ => error(span, "Attribute name literal must be a string"),
+            };
+        }
+        Some(TokenTree::Punct(punct)) if matches!(punct.as_char(), '@' | ':') => {
+            name.push(punct.as_char());
+            match group.next() {
+                Some(TokenTree::Ident(ident)) => name += &ident.to_string(),
+                token => {
+                    return error(
+                        token.map_or(punct.span(), |token| token.span()),
+                        format!("Expected ident after `{}` in attribute name", punct),
+                    )
                 }
             }
+            punct.span()
+        }
+        Some(TokenTree::Ident(ident)) => {
+            name += &ident.to_string();
+            ident.span()
+        }
+        Some(token) => {
+            return error(
+                token.span(),
+                format!("Expected attribute name, found `{}`", token),
+            )
+        }
+        None => return error(Span::call_site(), "Missing attribute name"),
+    };
 
-            TokenTree::Literal(literal) => match Lit::new(literal) {
-                Lit::Str(string) => {
-                    nodes.push(Node::Literal(string.value()));
+    while let Some(TokenTree::Punct(punct)) = group.peek() {
+        let separator = punct.clone();
+        if !matches!(separator.as_char(), '-' | ':' | '.') {
+            break;
+        }
+        group.next();
+        name.push(separator.as_char());
+        match group.next() {
+            Some(TokenTree::Ident(ident)) => name += &ident.to_string(),
+            Some(TokenTree::Literal(literal)) if literal.to_string().parse::<u32>().is_ok() => {
+                name += &literal.to_string()
+            }
+            token => {
+                return error(
+                    token.map_or(separator.span(), |token| token.span()),
+                    format!(
+                        "Expected ident after `{}` in attribute name `{}`",
+                        separator, name
+                    ),
+                )
+            }
+        }
+    }
+
+    Ok((name, span))
+}
+
+/// Parse a function call, after `@`
+///
+/// Eg. `@name[arguments]`, `@path::to::name[arguments] { children }`, `@name?[arguments]`
+///
+/// Children may contain named slots, which are given as named props. Eg. `@name[] { #slot { ... } }`
+///
+/// Or a struct which implements `Component`, if name is capitalized. Eg. `@Card { title: "Hi" }`
+fn parse_function(at: Punct, tokens: &mut Tokens) -> syn::Result<Node> {
+    enum PrevToken {
+        Nothing,
+        Ident,
+        FirstColon,
+        SecondColon,
+    }
+
+    let mut name = TokenStream::new();
+    let mut last_segment = String::new();
+    let mut prev_token = PrevToken::Nothing;
+    while let Some(token) = tokens.peek() {
+        match token {
+            TokenTree::Ident(ident) => match prev_token {
+                PrevToken::Nothing => {
+                    name.extend(quote! { #ident });
+                    last_segment = ident.to_string();
+                    prev_token = PrevToken::Ident;
+                    tokens.next();
+                }
+                PrevToken::Ident => break,
+                PrevToken::FirstColon => {
+                    return error(ident.span(), "Expected `::` in function path, found `:`");
+                }
+                PrevToken::SecondColon => {
+                    name.extend(quote! { :: #ident });
+                    last_segment = ident.to_string();
+                    prev_token = PrevToken::Ident;
+                    tokens.next();
                 }
-                _ => panic!("Only string literals are allowed"),
             },
 
-            TokenTree::Punct(punct) if punct.to_string() == "@" => {
-                enum PrevToken {
-                    Nothing,
-                    Ident,
-                    FirstColon,
-                    SecondColon,
-                }
-
-                let mut name = TokenStream::new();
-                let mut prev_token = PrevToken::Nothing;
-                while let Some(token) = tokens.peek() {
-                    match token {
-                        TokenTree::Ident(ident) => match prev_token {
-                            PrevToken::Nothing => {
-                                name.extend(quote! { #ident });
-                                prev_token = PrevToken::Ident;
-                                tokens.next();
-                            }
-                            PrevToken::Ident => break,
-                            PrevToken::FirstColon => {
-                                panic!("please use two colons please");
-                            }
-                            PrevToken::SecondColon => {
-                                name.extend(quote! { :: #ident });
-                                prev_token = PrevToken::Ident;
-                                tokens.next();
-                            }
-                        },
+            TokenTree::Punct(punct) if punct.as_char() == ':' => match prev_token {
+                PrevToken::Nothing => {
+                    name.extend(quote! { :: });
+                    prev_token = PrevToken::SecondColon;
+                    tokens.next();
+                }
+                PrevToken::Ident => {
+                    prev_token = PrevToken::FirstColon;
+                    tokens.next();
+                }
+                PrevToken::FirstColon => {
+                    prev_token = PrevToken::SecondColon;
+                    tokens.next();
+                }
+                PrevToken::SecondColon => break,
+            },
+            _ => break,
+        }
+    }
 
-                        TokenTree::Punct(punct) if punct.to_string() == ":" => match prev_token {
-                            PrevToken::Nothing => {
-                                name.extend(quote! { :: });
-                                prev_token = PrevToken::SecondColon;
-                                tokens.next();
-                            }
-                            PrevToken::Ident => {
-                                prev_token = PrevToken::FirstColon;
-                                tokens.next();
-                            }
-                            PrevToken::FirstColon => {
-                                prev_token = PrevToken::SecondColon;
-                                tokens.next();
-                            }
-                            PrevToken::SecondColon => break,
-                        },
-                        _ => break,
-                    }
+    if name.is_empty() {
+        return error(at.span(), "Expected function name after `@`");
+    }
+
+    // Propagate error of fallible function. Eg. `@name?[arguments]`
+    let fallible = match tokens.peek() {
+        Some(TokenTree::Punct(punct)) if punct.as_char() == '?' => {
+            let punct = punct.clone();
+            tokens.next();
+            Some(punct)
+        }
+        _ => None,
+    };
+
+    // Struct component, with fields. Eg. `@Card { title: "Hello" }`
+    if last_segment.starts_with(|ch: char| ch.is_ascii_uppercase()) {
+        if let Some(TokenTree::Group(group)) = tokens.peek() {
+            if group.delimiter() == Delimiter::Brace {
+                if let Some(fallible) = fallible {
+                    return error(fallible.span(), "Struct component cannot be fallible");
                 }
+                let fields = group.stream();
+                tokens.next();
+                return Ok(Node::Struct(name, fields));
+            }
+        }
+    }
 
-                let arguments = match tokens.peek() {
-                    Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Bracket => {
-                        let arguments = group.stream();
-                        tokens.next();
-
-                        // Remove trailing comma
-                        // Because comma is automatically inserted between 'attribute-like params'
-                        // and 'child-like View param'
-                        // Stream is reversed, first item removed (if is comma), and reversed back
-                        let mut stream_rev: Vec<TokenTree> = arguments.into_iter().collect();
-                        stream_rev.reverse();
-                        let mut stream_rev = stream_rev.into_iter().peekable();
-                        match stream_rev.peek() {
-                            Some(TokenTree::Punct(punct)) if punct.to_string() == "," => {
-                                stream_rev.next();
-                            }
-                            _ => (),
-                        }
-                        let mut arguments: Vec<TokenTree> = stream_rev.collect();
-                        arguments.reverse();
-                        Some(arguments.into_iter().collect())
-                    }
-                    _ => None,
-                };
+    let arguments = match tokens.peek() {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Bracket => {
+            let arguments = group.stream();
+            tokens.next();
+
+            // Remove trailing comma
+            // Because comma is automatically inserted between 'attribute-like params'
+            // and 'child-like View param'
+            let mut arguments: Vec<TokenTree> = arguments.into_iter().collect();
+            if is_punct(arguments.last(), ',') {
+                arguments.pop();
+            }
+            Some(arguments.into_iter().collect())
+        }
+        _ => None,
+    };
+
+    // Peek next token, don't consume unless matched
+    let (children, slots) = match tokens.peek() {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => {
+            // Parse, and then consume iterator item, then returned parsed value
+            let children = parse_view_with_slots(group.stream());
+            tokens.next();
+            let (children, slots) = children?;
+            (Some(children), slots)
+        }
+        _ => (None, Vec::new()),
+    };
+
+    let arguments = match arguments {
+        Some(arguments) => arguments,
+        None => {
+            if children.is_some() {
+                TokenStream::new()
+            } else {
+                return error(
+                    name.span(),
+                    "Function call must include argument group `[...]` (even empty), if it does not have children",
+                );
+            }
+        }
+    };
+
+    // Named slots are passed as named props
+    let arguments = match parse_named_props(&arguments)? {
+        Some(named_props) => Arguments::Named(props_path(&name), named_props, slots),
+        None if slots.is_empty() => Arguments::Positional(arguments),
+        None if arguments.is_empty() => Arguments::Named(props_path(&name), Vec::new(), slots),
+        None => return error(
+            arguments.span(),
+            "Named slots can only be used with named props. Eg. `[name = value]`, or `[flag, ..]`",
+        ),
+    };
+
+    Ok(Node::Function(Function {
+        name,
+        fallible,
+        arguments,
+        children,
+    }))
+}
 
-                // Peek next token, don't consume unless matched
-                let children = match tokens.peek() {
-                    Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => {
-                        // Parse, and then consume iterator item, then returned parsed value
-                        let children = Some(parse_view(group.stream()));
-                        tokens.next();
-                        children
-                    }
-                    _ => None,
-                };
+/// Parse named props of a component, if any prop is given as `name = value`, or the props end with
+/// `..`
+///
+/// Other props must be a name only, which is a flag. Eg. `[title = "Hello", compact]`.
+/// A trailing `..` uses named props without any `name = value`. Eg. `[compact, ..]`, `[..]`
+///
+/// Returns `None` if arguments are positional
+fn parse_named_props(arguments: &TokenStream) -> syn::Result<Option<Vec<NamedProp>>> {
+    // Split arguments by top-level commas
+    let mut parts = vec![Vec::new()];
+    for token in arguments.clone() {
+        match token {
+            TokenTree::Punct(punct) if punct.as_char() == ',' => parts.push(Vec::new()),
+            token => parts.last_mut().expect("should not be empty").push(token),
+        }
+    }
+    parts.retain(|part| !part.is_empty());
+
+    let is_rest = |part: &[TokenTree]| {
+        matches!(
+            part,
+            [TokenTree::Punct(first), TokenTree::Punct(second)]
+                if first.as_char() == '.' && first.spacing() == Spacing::Joint
+                    && second.as_char() == '.'
+        )
+    };
+    let has_rest = parts.last().is_some_and(|part| is_rest(part));
+    if has_rest {
+        parts.pop();
+    }
+    if let Some(part) = parts.iter().find(|part| is_rest(part)) {
+        return error(part[0].span(), "`..` must be after all other props");
+    }
 
-                let arguments = match arguments {
-                    Some(arguments) => arguments,
-                    None => {
-                        if children.is_some() {
-                            TokenStream::new()
-                        } else {
-                            panic!("function call must include argument group (even empty), if does not children");
-                        }
-                    }
-                };
+    // `==` is a joint punct, so is not counted
+    let is_named = |part: &[TokenTree]| {
+        matches!(
+            part,
+            [TokenTree::Ident(_), TokenTree::Punct(punct), _, ..]
+                if punct.as_char() == '=' && punct.spacing() == Spacing::Alone
+        )
+    };
+    if !has_rest && !parts.iter().any(|part| is_named(part)) {
+        return Ok(None);
+    }
 
-                nodes.push(Node::Function(Function {
-                    name,
-                    arguments,
-                    children,
-                }));
-            }
-
-            TokenTree::Group(group) if group.delimiter() == Delimiter::Bracket => {
-                let mut stream = group.stream().into_iter().peekable();
-
-                // Special `if` and `for` statements
-                if let Some(TokenTree::Punct(punct)) = stream.peek() {
-                    if punct.to_string() == ":" {
-                        stream.next();
-
-                        let Some(TokenTree::Ident(statement)) = stream.next() else {
-                            panic!("Missing or invalid statement");
-                        };
-                        match statement.to_string().as_str() {
-                            "if" => {
-                                // reverse tokenstream to consume from end
-                                // cannot use `.rev` as TokenStream as an iterator is not
-                                // double-ended
-                                let mut stream_rev: Vec<TokenTree> = stream.collect();
-                                stream_rev.reverse();
-                                let mut stream_rev = stream_rev.into_iter().peekable();
-
-                                // match last block
-                                // this could be the block after `if` or `else`
-                                let last_block = match stream_rev.next() {
-                                    Some(TokenTree::Group(group))
-                                        if group.delimiter() == Delimiter::Brace =>
-                                    {
-                                        parse_view(group.stream())
-                                    }
-                                    _ => panic!("`if` block must be group (matching last token)"),
-                                };
-
-                                // match `else` keyword before last block
-                                // if found, last block must be `else` block
-                                // otherwise, last block must be `if` block
-                                let (then, otherwise) = match stream_rev.peek() {
-                                    Some(TokenTree::Ident(ident))
-                                        if ident == "else" =>
-                                    {
-                                        stream_rev.next();
-                                        let then = match stream_rev.next() {
-                                            Some(TokenTree::Group(group))
-                                                if group.delimiter() == Delimiter::Brace =>
-                                            {
-                                                parse_view(group.stream())
-                                            }
-                                            _ => panic!("`if` block must be group (matching third last token)"),
-                                        };
-                                        (then, Some(last_block))
-                                    }
-                                    _ => (last_block, None),
-                                };
-
-                                // reverse back and return to tokenstream
-                                // everything before blocks matched above, is part of `if`
-                                // condition
-                                let mut condition: Vec<TokenTree> = stream_rev.collect();
-                                condition.reverse();
-                                let condition: TokenStream = condition.into_iter().collect();
-
-                                nodes.push(Node::If(condition, then, otherwise));
-                            }
+    let mut named_props = Vec::new();
+    let mut errors = Errors::default();
+    for part in parts {
+        match part.as_slice() {
+            [TokenTree::Ident(name)] => named_props.push(NamedProp {
+                name: name.clone(),
+                value: None,
+            }),
+            [TokenTree::Ident(name), _, value @ ..] if is_named(&part) => {
+                named_props.push(NamedProp {
+                    name: name.clone(),
+                    value: Some(value.iter().cloned().collect()),
+                })
+            }
+            _ => errors.push(syn::Error::new(
+                part[0].span(),
+                "Expected named prop `name = value`, or flag `name`, because props are named",
+            )),
+        }
+    }
+    errors.finish(Some(named_props))
+}
 
-                            "for" => {
-                                // reverse tokenstream to consume from end
-                                // cannot use `.rev` as TokenStream as an iterator is not
-                                // double-ended
-                                let mut stream_rev: Vec<TokenTree> = stream.collect();
-                                stream_rev.reverse();
-                                let mut stream_rev = stream_rev.into_iter().peekable();
-
-                                // match last block
-                                // this must be the block of the `for` loop (obviously)
-                                let block = match stream_rev.next() {
-                                    Some(TokenTree::Group(group))
-                                        if group.delimiter() == Delimiter::Brace =>
-                                    {
-                                        parse_view(group.stream())
-                                    }
-                                    _ => panic!("`if` block must be group (matching last token)"),
-                                };
-
-                                // reverse back and return to tokenstream
-                                // this must be the 'source' of the `for` loop (between `for` and
-                                // block)
-                                let mut source: Vec<TokenTree> = stream_rev.collect();
-                                source.reverse();
-                                let source: TokenStream = source.into_iter().collect();
-
-                                nodes.push(Node::For(source, block));
-                            }
+/// Path of the props struct of a component, generated by `#[component]`
+///
+/// Eg. `path::to::post_card` -> `path::to::PostCardProps`
+fn props_path(name: &TokenStream) -> TokenStream {
+    let mut tokens: Vec<TokenTree> = name.clone().into_iter().collect();
+    if let Some(TokenTree::Ident(ident)) = tokens.pop() {
+        let props = props_name(&ident.to_string());
+        tokens.push(TokenTree::Ident(Ident::new(&props, ident.span())));
+    }
+    tokens.into_iter().collect()
+}
 
-                            "where" => {
-                                // reverse tokenstream to consume from end
-                                // cannot use `.rev` as TokenStream as an iterator is not
-                                // double-ended
-                                let mut stream_rev: Vec<TokenTree> = stream.collect();
-                                stream_rev.reverse();
-                                let mut stream_rev = stream_rev.into_iter().peekable();
-
-                                // match last block
-                                // this must be the block of the `where` statement (obviously)
-                                let block = match stream_rev.next() {
-                                    Some(TokenTree::Group(group))
-                                        if group.delimiter() == Delimiter::Brace =>
-                                    {
-                                        parse_view(group.stream())
-                                    }
-                                    _ => {
-                                        panic!("`where` block must be group (matching last token)")
-                                    }
-                                };
-
-                                // reverse back and return to tokenstream
-                                // this is the `let` statements for the `where` statement
-                                let mut statements: Vec<TokenTree> = stream_rev.collect();
-                                statements.reverse();
-                                let statements: TokenStream = statements.into_iter().collect();
-
-                                nodes.push(Node::With(statements, block));
-                            }
+/// Parse a special statement, after `[:`
+///
+/// Eg. `[:if condition { ... }]`, `[:for item in items { ... }]`, `[:context value { ... }]`
+fn parse_statement(span: Span, mut stream: Tokens) -> syn::Result<Node> {
+    let statement = match stream.next() {
+        Some(TokenTree::Ident(statement)) => statement,
+        token => return error(
+            token.map_or(span, |token| token.span()),
+            "Missing or invalid statement. Expected `if`, `for`, `match`, `where`, or `context`",
+        ),
+    };
+
+    match statement.to_string().as_str() {
+        "if" => parse_if(statement, stream),
+
+        "for" => {
+            let (source, block) = split_last_block(&statement, stream)?;
+            // this must be the 'source' of the `for` loop (between `for` and
+            // block)
+            if source.is_empty() {
+                return error(
+                    statement.span(),
+                    "Expected pattern and iterator for `for` loop",
+                );
+            }
+            Ok(Node::For(source, block))
+        }
 
-                            _ => panic!("Invalid statement"),
-                        }
-                        continue;
-                    }
+        "match" => parse_match(statement, stream),
+
+        "where" => {
+            // this is the `let` statements for the `where` statement
+            let (statements, block) = split_last_block(&statement, stream)?;
+            Ok(Node::With(statements, block))
+        }
+
+        "context" => {
+            let (value, block) = split_last_block(&statement, stream)?;
+            if value.is_empty() {
+                return error(statement.span(), "Expected value for `context`");
+            }
+            Ok(Node::Context(value, block))
+        }
+
+        _ => error(
+            statement.span(),
+            format!(
+                "Invalid statement `{}`. Expected `if`, `for`, `match`, `where`, or `context`",
+                statement
+            ),
+        ),
+    }
+}
+
+/// Parse an `if` statement, with any amount of `else if` branches, and optional `else` block
+///
+/// Eg. `[:if a { ... } else if let Some(b) = c { ... } else { ... }]`
+fn parse_if(statement: Ident, mut stream: Tokens) -> syn::Result<Node> {
+    let mut branches = Vec::new();
+    let mut keyword = statement;
+
+    loop {
+        // condition is everything before the block
+        // block is the first brace group, which is followed by `else` or the end of the statement
+        // this allows braces in the condition, Eg. `if match x { ... } { ... }`
+        let mut condition = TokenStream::new();
+        let block = loop {
+            match stream.next() {
+                Some(TokenTree::Group(group))
+                    if group.delimiter() == Delimiter::Brace
+                        && (stream.peek().is_none()
+                            || matches!(stream.peek(), Some(TokenTree::Ident(ident)) if ident == "else")) =>
+                {
+                    break group;
                 }
+                Some(token) => condition.extend([token]),
+                None => {
+                    return error(
+                        keyword.span(),
+                        "Expected block with braces `{...}` at end of `if` statement",
+                    )
+                }
+            }
+        };
+        if condition.is_empty() {
+            return error(keyword.span(), "Expected condition for `if` statement");
+        }
+        branches.push((condition, parse_view(block.stream())?));
 
-                nodes.push(Node::Expression(stream.collect()));
+        // `else` keyword, or end of statement
+        let Some(TokenTree::Ident(else_token)) = stream.next() else {
+            return Ok(Node::If(branches, None));
+        };
+
+        match stream.next() {
+            // `else if`, continue chain
+            Some(TokenTree::Ident(ident)) if ident == "if" => keyword = ident,
+
+            // `else`, end of chain
+            Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => {
+                if let Some(token) = stream.next() {
+                    return error(token.span(), "Unexpected token after `else` block");
+                }
+                return Ok(Node::If(branches, Some(parse_view(group.stream())?)));
             }
 
-            // Tilde for whitespace
-            TokenTree::Punct(punct) if punct.to_string() == "~" => {
-                // Double tilde for linebreak
-                if let Some(TokenTree::Punct(punct)) = tokens.peek() {
-                    if punct.to_string() == "~" {
-                        tokens.next();
-                        nodes.push(Node::Literal("\n".to_string()));
-                        continue;
-                    }
+            token => {
+                return error(
+                    token.map_or(else_token.span(), |token| token.span()),
+                    "Expected `if` or block with braces `{...}` after `else`",
+                )
+            }
+        }
+    }
+}
+
+/// Parse a `match` statement, where each arm is a block
+///
+/// Eg. `[:match x { Some(y) if y > 2 => { ... }, _ => { ... } }]`
+fn parse_match(statement: Ident, stream: Tokens) -> syn::Result<Node> {
+    // reverse tokenstream to consume from end
+    let mut stream_rev: Vec<TokenTree> = stream.collect();
+    stream_rev.reverse();
+    let mut stream_rev = stream_rev.into_iter();
+
+    // last block contains match arms
+    let arms_group = match stream_rev.next() {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => group,
+        token => {
+            return error(
+                token.map_or(statement.span(), |token| token.span()),
+                "Expected block with braces `{...}` at end of `match` statement",
+            )
+        }
+    };
+
+    // everything before block is the scrutinee
+    let scrutinee = collect_reversed(stream_rev);
+    if scrutinee.is_empty() {
+        return error(
+            statement.span(),
+            "Expected expression for `match` statement",
+        );
+    }
+
+    let mut arms = Vec::new();
+    let mut errors = Errors::default();
+    let mut tokens = arms_group.stream().into_iter().peekable();
+
+    while tokens.peek().is_some() {
+        // pattern (and guard) is everything before `=>`
+        let mut pattern = TokenStream::new();
+        let arrow = loop {
+            match tokens.next() {
+                Some(TokenTree::Punct(punct))
+                    if punct.as_char() == '=' && is_punct(tokens.peek(), '>') =>
+                {
+                    tokens.next();
+                    break Some(punct);
                 }
-                nodes.push(Node::Literal(" ".to_string()));
+                Some(token) => pattern.extend([token]),
+                None => break None,
             }
+        };
+        let Some(arrow) = arrow else {
+            errors.push(syn::Error::new(
+                pattern.span(),
+                "Expected `=>` after pattern of `match` arm",
+            ));
+            break;
+        };
+        if pattern.is_empty() {
+            errors.push(syn::Error::new(
+                arrow.span(),
+                "Expected pattern before `=>` of `match` arm",
+            ));
+        }
 
-            // Html entity
-            TokenTree::Punct(punct) if punct.to_string() == "&" => {
-                let code = match tokens.next() {
-                    Some(TokenTree::Ident(ident)) => ident.to_string(),
-                    Some(TokenTree::Literal(literal))
-                        if literal.to_string().parse::<i32>().is_ok() =>
-                    {
-                        literal.to_string()
-                    }
-                    _ => panic!("Expected html entity code, as ident or codepoint integer literal"),
-                };
-                // optional semicolon after code
-                match tokens.peek() {
-                    Some(TokenTree::Punct(punct)) if punct.to_string() == ";" => {
-                        tokens.next();
+        match tokens.next() {
+            Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => {
+                if let Some(view) = errors.take(parse_view(group.stream())) {
+                    arms.push((pattern, view));
+                }
+            }
+            token => {
+                errors.push(syn::Error::new(
+                    token.map_or(arrow.span(), |token| token.span()),
+                    "Expected block with braces `{...}` after `=>` of `match` arm",
+                ));
+                // Skip to next arm
+                for token in tokens.by_ref() {
+                    if is_punct(Some(&token), ',') {
+                        break;
                     }
-                    _ => (),
                 }
-                nodes.push(Node::Literal(format!("&{};", code)));
+                continue;
             }
+        }
 
-            _ => {
-                panic!("Unexpected token {:#?}", token);
-            }
+        // optional comma between arms
+        if is_punct(tokens.peek(), ',') {
+            tokens.next();
+        }
+    }
+
+    errors.finish(Node::Match(scrutinee, arms))
+}
+
+/// Split a statement into the tokens before the last block, and the parsed last block
+fn split_last_block(statement: &impl Spanned, stream: Tokens) -> syn::Result<(TokenStream, View)> {
+    // reverse tokenstream to consume from end
+    // cannot use `.rev` as TokenStream as an iterator is not
+    // double-ended
+    let mut stream_rev: Vec<TokenTree> = stream.collect();
+    stream_rev.reverse();
+    let mut stream_rev = stream_rev.into_iter();
+
+    let block = parse_last_block(statement, stream_rev.next())?;
+    Ok((collect_reversed(stream_rev), block))
+}
+
+/// Parse the block at the end of a statement
+///
+/// `previous` is used for error span, if block is missing
+fn parse_last_block(previous: &impl Spanned, token: Option<TokenTree>) -> syn::Result<View> {
+    match token {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => {
+            parse_view(group.stream())
         }
+        Some(token) => error(
+            token.span(),
+            "Expected block with braces `{...}` at end of statement",
+        ),
+        None => error(
+            previous.span(),
+            "Expected block with braces `{...}` at end of statement",
+        ),
     }
+}
 
-    View(nodes)
+/// Reverse tokens back to original order, and collect into a tokenstream
+fn collect_reversed(stream_rev: impl Iterator<Item = TokenTree>) -> TokenStream {
+    let mut tokens: Vec<TokenTree> = stream_rev.collect();
+    tokens.reverse();
+    tokens.into_iter().collect()
 }
@@ -0,0 +1,146 @@
+//! Compare views with static nodes (rendered at compile time) against the same views built at
+//! runtime
+//!
+//! Run with `cargo bench --bench static_views`
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use ibex::prelude::*;
+use ibex::render::RenderOptions;
+
+const ITERATIONS: u32 = 2_000;
+
+fn main() {
+    let title = "Changelog";
+    let minify = RenderOptions {
+        minify: true,
+        ..Default::default()
+    };
+
+    let precompiled = page(title).render_document(Some("en"));
+    let runtime = runtime_page(title).render_document(Some("en"));
+    assert_eq!(
+        precompiled, runtime,
+        "static nodes should render the same as runtime nodes"
+    );
+    let precompiled_minified = page(title).render_document_with(Some("en"), &minify);
+    let runtime_minified = runtime_page(title).render_document_with(Some("en"), &minify);
+    assert_eq!(
+        precompiled_minified, runtime_minified,
+        "minified static nodes should render the same as runtime nodes"
+    );
+    println!(
+        "Output: {} bytes, {} bytes minified (both)",
+        precompiled.len(),
+        precompiled_minified.len()
+    );
+
+    let precompiled = time(|| page(black_box(title)).render_document(Some("en")));
+    let runtime = time(|| runtime_page(black_box(title)).render_document(Some("en")));
+    let precompiled_minified =
+        time(|| page(black_box(title)).render_document_with(Some("en"), &minify));
+    let runtime_minified =
+        time(|| runtime_page(black_box(title)).render_document_with(Some("en"), &minify));
+
+    println!("Static nodes:  {:?} per page", precompiled / ITERATIONS);
+    println!("Runtime nodes: {:?} per page", runtime / ITERATIONS);
+    println!(
+        "Static nodes:  {:?} per page (minified)",
+        precompiled_minified / ITERATIONS
+    );
+    println!(
+        "Runtime nodes: {:?} per page (minified)",
+        runtime_minified / ITERATIONS
+    );
+}
+
+/// Total time to run function many times
+fn time<T>(f: impl Fn() -> T) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(f());
+    }
+    start.elapsed()
+}
+
+/// Same page, with every node built at runtime, as if `view!` did not render static nodes
+///
+/// Static nodes are replaced in place by building their nodes, without cloning the rest of the
+/// page
+fn runtime_page(title: &str) -> View {
+    let mut view = page(title);
+    build_static(&mut view);
+    view
+}
+
+fn build_static(view: &mut View) {
+    for node in &mut view.0 {
+        match node {
+            Node::Static(static_view) => {
+                let mut built = (static_view.build)();
+                build_static(&mut built);
+                *node = Node::Fragment(built);
+            }
+            Node::HeadAppend(view) | Node::BodyEndAppend(view) | Node::Fragment(view) => {
+                build_static(view)
+            }
+            Node::Element(element) => build_static(&mut element.children),
+            Node::DocumentAttributes(_, _) | Node::Text(_) | Node::Raw(_) => (),
+        }
+    }
+}
+
+fn page(title: &str) -> View {
+    view! {
+        HEAD {
+            meta [charset="utf-8"]/
+            title { [title] }
+            link [rel="stylesheet", href="/css/main.css"]/
+        }
+        header {
+            nav {
+                ul {
+                    li { a [href="/"] { "Home" } }
+                    li { a [href="/blog"] { "Blog" } }
+                    li { a [href="/changelog"] { "Changelog" } }
+                }
+            }
+        }
+        main {
+            h1 { [title] }
+            [:for i in 0..100 {
+                @release[i]
+            }]
+        }
+        @footer[]
+    }
+}
+
+fn release(version: u32) -> View {
+    view! {
+        section ."release" {
+            h2 { "Version " [version.to_string()] }
+            ul {
+                li { "Fixed escaping of " code { "<script>" } " contents" }
+                li { "Added " em { "pretty" } " and " em { "minified" } " output" }
+                li { "Improved compile errors for " code { "view!" } }
+            }
+        }
+    }
+}
+
+fn footer() -> View {
+    view! {
+        footer {
+            p { "Made with " a [href="https://github.com/dxrcy/ibex"] { "Ibex" } }
+            ul ."links" {
+                li { a [href="/about"] { "About" } }
+                li { a [href="/contact"] { "Contact" } }
+                li { a [href="/privacy"] { "Privacy" } }
+                li { a [href="/rss.xml"] { "RSS" } }
+            }
+            p ."copyright" { "Copyright " &copy; " 2024" }
+        }
+    }
+}
@@ -4,16 +4,18 @@ pub mod extras;
 pub mod ssg;
 
 pub use extras::{is_local, use_meta, Meta};
+pub use ibex_core::compose::Component;
 pub use ibex_core::*;
-pub use ibex_macros::{document, view};
+pub use ibex_macros::{component, document, try_view, view, IntoView};
 
 pub mod prelude {
     pub use ibex_core::{
-        compose::{Attribute, Element, Node, Tag, View},
+        compose::{Attribute, ClassList, Component, Element, Node, PreEscaped, Style, Tag, View},
+        context::{use_context, with_context},
         dom::Document,
     };
 
-    pub use ibex_macros::{document, view};
+    pub use ibex_macros::{component, document, try_view, view, IntoView};
 
     pub use crate::extras::{is_local, use_meta, Meta};
     pub use crate::url;
@@ -1,15 +1,20 @@
 use std::{
-    fs, io,
+    fs,
+    io::{self, BufWriter, Write},
     path::Path,
     time::{SystemTime, UNIX_EPOCH},
 };
 
-use crate::{compose::View, dom::Document};
+use crate::{
+    compose::{PreEscaped, View},
+    dom::Document,
+    render::RenderOptions,
+};
 
 pub fn use_autoreload() -> View {
     const AUTORELOAD_SCRIPT: &str = include_str!("autoreload.html");
     if crate::is_local() {
-        AUTORELOAD_SCRIPT.into()
+        PreEscaped(AUTORELOAD_SCRIPT).into()
     } else {
         View::new()
     }
@@ -41,22 +46,54 @@ impl Route {
         }
     }
     pub fn render(self) -> RouteFile {
-        let paths = self
-            .url_paths
+        RouteFile {
+            paths: self.file_paths(),
+            content: self.content.render(),
+        }
+    }
+
+    /// Render directly into files in build directory, without an intermediate string
+    ///
+    /// Content is rendered once, and copied for any additional paths
+    pub fn write(&self) -> io::Result<()> {
+        self.write_with(&RenderOptions::default())
+    }
+    /// Render with options directly into files in build directory
+    ///
+    /// See `write`
+    pub fn write_with(&self, options: &RenderOptions) -> io::Result<()> {
+        let mut paths = self
+            .file_paths()
             .into_iter()
+            .map(|path| format!("{BUILD_DIR}/{}", path));
+        let Some(first_path) = paths.next() else {
+            return Ok(());
+        };
+
+        create_parent_folder(&first_path)?;
+        let mut file = BufWriter::new(fs::File::create(&first_path)?);
+        self.content.render_to_io_with(options, &mut file)?;
+        file.flush()?;
+
+        for path in paths {
+            create_parent_folder(&path)?;
+            fs::copy(&first_path, path)?;
+        }
+        Ok(())
+    }
+
+    /// Get file paths (relative to build directory) from url paths
+    fn file_paths(&self) -> Vec<String> {
+        self.url_paths
+            .iter()
             .map(|url_path| {
                 if self.content.is_document() {
-                    url_path_to_filepath(&url_path)
+                    url_path_to_filepath(url_path)
                 } else {
-                    url_path
+                    url_path.clone()
                 }
             })
-            .collect();
-
-        RouteFile {
-            paths,
-            content: self.content.render(),
-        }
+            .collect()
     }
 }
 
@@ -73,6 +110,23 @@ impl RouteContent {
             Self::Raw(content) => content,
         }
     }
+    /// Render directly into an IO writer, such as a file or network stream
+    pub fn render_to_io(&self, w: impl io::Write) -> io::Result<()> {
+        self.render_to_io_with(&RenderOptions::default(), w)
+    }
+    /// Render with options directly into an IO writer
+    ///
+    /// Options only apply to documents, not raw content
+    pub fn render_to_io_with(
+        &self,
+        options: &RenderOptions,
+        mut w: impl io::Write,
+    ) -> io::Result<()> {
+        match self {
+            Self::Document(document) => document.render_to_io_with(options, w),
+            Self::Raw(content) => w.write_all(content.as_bytes()),
+        }
+    }
     fn is_document(&self) -> bool {
         matches!(self, Self::Document(_))
     }
@@ -126,8 +180,23 @@ const STATIC_ROOT_DIR: &str = "build/static/_root";
 const SCSS_DIR: &str = "src/scss";
 
 pub fn quick_build(routes: Vec<Route>) -> io::Result<()> {
-    let files = render_routes(routes);
-    write_files(files)?;
+    quick_build_with(routes, &RenderOptions::default())
+}
+
+/// Same as `quick_build`, but render documents with options
+///
+/// Eg. readable output for local builds, and minified output when deployed:
+///
+/// ```ignore
+/// let options = if is_local() {
+///     RenderOptions::pretty(2)
+/// } else {
+///     RenderOptions::minified()
+/// };
+/// ssg::quick_build_with(routes, &options)
+/// ```
+pub fn quick_build_with(routes: Vec<Route>, options: &RenderOptions) -> io::Result<()> {
+    write_routes_with(routes, options)?;
     if Path::new(STATIC_DIR).exists() {
         copy_static()?;
     }
@@ -155,11 +224,25 @@ pub fn render_route(route: Route) -> RouteFile {
     route.render()
 }
 
-pub fn write_files(files: Vec<RouteFile>) -> Result<(), io::Error> {
-    if Path::new(BUILD_DIR).exists() {
-        fs::remove_dir_all(BUILD_DIR)?;
+/// Render routes directly into files in build directory
+///
+/// Unlike `render_routes` and `write_files`, rendered content is not kept in memory
+pub fn write_routes(routes: Vec<Route>) -> io::Result<()> {
+    write_routes_with(routes, &RenderOptions::default())
+}
+/// Render routes with options directly into files in build directory
+///
+/// See `write_routes`
+pub fn write_routes_with(routes: Vec<Route>, options: &RenderOptions) -> io::Result<()> {
+    recreate_build_dir()?;
+    for route in routes {
+        route.write_with(options)?;
     }
-    fs::create_dir_all(BUILD_DIR)?;
+    Ok(())
+}
+
+pub fn write_files(files: Vec<RouteFile>) -> Result<(), io::Error> {
+    recreate_build_dir()?;
 
     for file in files {
         for path in file.paths {
@@ -196,7 +279,7 @@ fn move_static_root() -> io::Result<()> {
     let children = fs::read_dir(static_root)?.flatten();
     for child in children {
         let dest_path = Path::new(build).join(child.file_name());
-        move_or_symlink_item(&child.path(), &dest_path, crate::is_local())?;
+        move_or_symlink_item(child.path(), &dest_path, crate::is_local())?;
     }
 
     if !crate::is_local() {
@@ -328,6 +411,14 @@ fn remove_leading_slash(path: &str) -> &str {
     chars.as_str()
 }
 
+/// Remove build directory if it exists, and create it empty
+fn recreate_build_dir() -> io::Result<()> {
+    if Path::new(BUILD_DIR).exists() {
+        fs::remove_dir_all(BUILD_DIR)?;
+    }
+    fs::create_dir_all(BUILD_DIR)
+}
+
 fn create_parent_folder(path: &str) -> Result<(), io::Error> {
     let mut path = path.split('/');
     path.next_back();
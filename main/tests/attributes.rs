@@ -0,0 +1,90 @@
+use ibex::prelude::*;
+
+#[test]
+fn style_shorthand_escapes_values() {
+    let width = 50;
+    let color = Some("red; display: none");
+    let accent = None::<&str>;
+    assert_eq!(
+        view! {
+            div [style={ width: format!("{}%", width), background-color: color, --accent: accent }] {}
+        }
+        .render_orphan(),
+        r#"<div style="width: 50%; background-color: red\3b  display: none"></div>"#
+    );
+    assert_eq!(
+        view! { div [style={ "font-family": "'Open Sans" }] {} }.render_orphan(),
+        r#"<div style="font-family: 'Open Sans'"></div>"#
+    );
+}
+
+#[test]
+fn style_shorthand_is_omitted_if_all_none() {
+    let color = None::<String>;
+    assert_eq!(
+        view! { div [style={ color: color }] {} }.render_orphan(),
+        "<div></div>"
+    );
+}
+
+#[test]
+fn chained_classes_are_joined() {
+    let name = "theme-dark";
+    assert_eq!(
+        view! { div.card.shadow."big wide".[name] {} }.render_orphan(),
+        r#"<div class="card shadow big wide theme-dark"></div>"#
+    );
+    assert_eq!(
+        view! { div.card."card shadow" {} }.render_orphan(),
+        r#"<div class="card shadow"></div>"#
+    );
+}
+
+#[test]
+fn conditional_classes_are_added_if_true() {
+    let is_active = true;
+    let visible = true;
+    assert_eq!(
+        view! { div.card.{ active: is_active, "is-hidden": !visible } {} }.render_orphan(),
+        r#"<div class="card active"></div>"#
+    );
+    assert_eq!(
+        view! { div.{ active: !is_active } [id="x"] {} }.render_orphan(),
+        r#"<div id="x"></div>"#
+    );
+}
+
+#[test]
+fn class_attributes_are_merged() {
+    let extra = "extra";
+    assert_eq!(
+        view! { div [id="x", class="a", class=extra] {} }.render_orphan(),
+        r#"<div id="x" class="a extra"></div>"#
+    );
+    assert_eq!(
+        view! { div.card [id="x", class="card b"] {} }.render_orphan(),
+        r#"<div class="card b" id="x"></div>"#
+    );
+}
+
+#[test]
+fn class_attributes_are_merged_when_rendered() {
+    let attribute = |name: &str, value: &str| Attribute {
+        name: name.to_string(),
+        value: Some(value.to_string()),
+    };
+    let element = Element {
+        tag: Tag::Div,
+        attributes: vec![
+            attribute("id", "x"),
+            attribute("class", "a b"),
+            attribute("title", "t"),
+            attribute("class", "b c"),
+        ],
+        children: View::new(),
+    };
+    assert_eq!(
+        View(vec![Node::Element(element)]).render_orphan(),
+        r#"<div id="x" class="a b c" title="t"></div>"#
+    );
+}
@@ -0,0 +1,139 @@
+use ibex::prelude::*;
+
+struct Post {
+    title: String,
+}
+
+#[component]
+fn card(title: &str, post: Option<&Post>, tags: &[&str]) -> View {
+    view! {
+        article {
+            h2 { [title] }
+            [:if let Some(post) = post { p { [&post.title] } }]
+            [:for tag in tags { span { [*tag] } }]
+        }
+    }
+}
+
+#[test]
+fn props_can_borrow() {
+    let post = Post {
+        title: "First post".to_string(),
+    };
+    let tags = ["rust", "html"];
+    assert_eq!(
+        view! { @card[title = "Hello", post = Some(&post), tags = &tags[..]] }.render_orphan(),
+        "<article><h2>Hello</h2><p>First post</p><span>rust</span><span>html</span></article>"
+    );
+    assert_eq!(
+        view! { @card[title = "Empty"] }.render_orphan(),
+        "<article><h2>Empty</h2></article>"
+    );
+}
+
+#[component]
+fn panel(title: String, compact: bool, children: View) -> View {
+    view! {
+        section [data-compact?=compact] {
+            [:if !title.is_empty() { h2 { [title] } }]
+            [children]
+        }
+    }
+}
+
+fn heading(big: bool) -> View {
+    view! { [:if big { h1 { "Big" } } else { h2 { "Small" } }] }
+}
+
+#[test]
+fn rest_selects_named_props() {
+    assert_eq!(
+        view! { @panel[compact, ..] }.render_orphan(),
+        "<section data-compact></section>"
+    );
+    assert_eq!(
+        view! { @panel[..] { p { "Contents" } } }.render_orphan(),
+        "<section><p>Contents</p></section>"
+    );
+    assert_eq!(
+        view! { @panel[title = "Hello", compact] }.render_orphan(),
+        "<section data-compact><h2>Hello</h2></section>"
+    );
+
+    let big = true;
+    assert_eq!(
+        view! { @heading[big] @heading[false] }.render_orphan(),
+        "<h1>Big</h1><h2>Small</h2>"
+    );
+}
+
+#[component]
+fn post_card(post: &Post, compact: bool, #[prop(default = 2)] max_title: usize) -> View {
+    let title: String = post.title.chars().take(max_title).collect();
+    view! { h3 [data-compact?=compact] { [title] } }
+}
+
+#[test]
+fn required_props_need_not_implement_default() {
+    let post = Post {
+        title: "Hello".to_string(),
+    };
+    assert_eq!(
+        view! { @post_card[post = &post] }.render_orphan(),
+        "<h3>He</h3>"
+    );
+    assert_eq!(
+        view! { @post_card[compact, max_title = 4usize, post = &post] }.render_orphan(),
+        "<h3 data-compact>Hell</h3>"
+    );
+}
+
+#[derive(IntoView)]
+struct Release {
+    version: &'static str,
+    #[ibex(label = "Notes")]
+    notes: String,
+    #[ibex(template = render_changes)]
+    changes: Vec<String>,
+}
+
+fn render_changes(changes: &[String]) -> View {
+    view! { ul { [:for change in changes { li { [change] } }] } }
+}
+
+#[derive(IntoView)]
+#[ibex(template = render_version)]
+struct Version(u32);
+
+fn render_version(version: &Version) -> View {
+    view! { "v" [version.0.to_string()] }
+}
+
+/// Owned impl is derived, even if a field cannot be rendered by reference
+#[derive(IntoView)]
+struct Tags {
+    tags: Vec<String>,
+}
+
+#[test]
+fn derived_component_renders_by_reference() {
+    let releases = vec![Release {
+        version: "0.2",
+        notes: "Faster".to_string(),
+        changes: vec!["Minify".to_string()],
+    }];
+    let versions = [Version(1), Version(2)];
+    assert_eq!(
+        view! { [:for r in &releases { [r] }] }.render_orphan(),
+        "<dl><dt>version</dt><dd>0.2</dd><dt>Notes</dt><dd>Faster</dd>\
+            <dt>changes</dt><dd><ul><li>Minify</li></ul></dd></dl>"
+    );
+    assert_eq!(
+        view! { [:for v in &versions { [v] " " }] }.render_orphan(),
+        "v1 v2 "
+    );
+    assert_eq!(
+        view! { [Tags { tags: vec!["a".to_string()] }] }.render_orphan(),
+        "<dl><dt>tags</dt><dd>a</dd></dl>"
+    );
+}
@@ -0,0 +1,32 @@
+use ibex::prelude::*;
+
+#[derive(Clone)]
+struct Theme(&'static str);
+
+fn button(label: &str) -> View {
+    let theme = use_context::<Theme>().map_or("light", |theme| theme.0);
+    view! { button [data-theme=theme] { [label] } }
+}
+
+fn toolbar(count: &str) -> Result<View, std::num::ParseIntError> {
+    try_view! {
+        [:context Theme("dark") {
+            @button["Save"]
+            [:context Theme("blue") {
+                span { [count.parse::<u32>()?] }
+            }]
+        }]
+        @button["Close"]
+    }
+}
+
+#[test]
+fn context_block_propagates_errors() {
+    assert_eq!(
+        toolbar("3").unwrap().render_orphan(),
+        r#"<button data-theme="dark">Save</button><span>3</span><button data-theme="light">Close</button>"#
+    );
+    assert!(toolbar("three").is_err());
+    // Context is removed when error is propagated
+    assert!(use_context::<Theme>().is_none());
+}
@@ -0,0 +1,82 @@
+use ibex::prelude::*;
+
+fn describe(image: Option<&str>, caption: Option<&str>, draft: bool) -> View {
+    view! {
+        [:if let Some(src) = image {
+            img [src=src]/
+        } else if let Some(caption) = caption {
+            p { [caption] }
+        } else if draft {
+            i { "Draft" }
+        } else {
+            "Nothing"
+        }]
+    }
+}
+
+#[test]
+fn else_if_let_chain() {
+    assert_eq!(
+        describe(Some("/a.png"), Some("A"), true).render_orphan(),
+        r#"<img src="/a.png">"#
+    );
+    assert_eq!(describe(None, Some("A"), true).render_orphan(), "<p>A</p>");
+    assert_eq!(describe(None, None, true).render_orphan(), "<i>Draft</i>");
+    assert_eq!(describe(None, None, false).render_orphan(), "Nothing");
+}
+
+enum Status {
+    Draft,
+    Edited(u32),
+    Published,
+}
+
+fn status(status: &Status, show_edits: bool) -> View {
+    view! {
+        [:match status {
+            Status::Draft => { i { "Draft" } }
+            Status::Edited(count) if show_edits && *count >= 2 => { i { "Edited " [count.to_string()] " times" } }
+            Status::Edited(_) if show_edits => { i { "Edited" } }
+            _ => { "Published" }
+        }]
+    }
+}
+
+#[test]
+fn match_guards() {
+    assert_eq!(status(&Status::Draft, true).render_orphan(), "<i>Draft</i>");
+    assert_eq!(
+        status(&Status::Edited(3), true).render_orphan(),
+        "<i>Edited 3 times</i>"
+    );
+    assert_eq!(
+        status(&Status::Edited(1), true).render_orphan(),
+        "<i>Edited</i>"
+    );
+    assert_eq!(
+        status(&Status::Edited(3), false).render_orphan(),
+        "Published"
+    );
+    assert_eq!(
+        status(&Status::Published, true).render_orphan(),
+        "Published"
+    );
+}
+
+#[test]
+fn match_arms_with_and_without_commas() {
+    let view = |n: u32| {
+        view! {
+            [:match n {
+                0 => { "zero" },
+                1 | 2 => { "few" }
+                n if n % 2 == 0 => { "even" },
+                _ => { "odd" }
+            }]
+        }
+    };
+    assert_eq!(view(0).render_orphan(), "zero");
+    assert_eq!(view(2).render_orphan(), "few");
+    assert_eq!(view(4).render_orphan(), "even");
+    assert_eq!(view(5).render_orphan(), "odd");
+}
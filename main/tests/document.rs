@@ -0,0 +1,108 @@
+use ibex::prelude::*;
+
+fn layout(children: View) -> View {
+    view! {
+        HTML [lang="en", dir="ltr"]/
+        BODY [class="layout"] {
+            main { [children] }
+        }
+    }
+}
+
+fn french_page() -> View {
+    view! {
+        HTML [lang="fr"]/
+        HEAD [prefix="og: https://ogp.me/ns#"] {
+            title { "Accueil" }
+        }
+        BODY [class="home", data-page="index"] {
+            p { "Bonjour" }
+        }
+    }
+}
+
+#[test]
+fn html_attributes_replace_lang() {
+    assert_eq!(
+        view! { HTML [lang="fr"]/ p { "Bonjour" } }.render_document(Some("en")),
+        r#"<!DOCTYPE html><html lang="fr"><head></head><body><p>Bonjour</p></body></html>"#
+    );
+    assert_eq!(
+        document! { [lang="en", dir="ltr"] HTML [lang="fr"]/ }.render(),
+        r#"<!DOCTYPE html><html lang="fr" dir="ltr"><head></head><body></body></html>"#
+    );
+}
+
+#[test]
+fn document_attributes_are_set_from_nested_components() {
+    assert_eq!(
+        view! { @layout[french_page()] }.render_document(Some("de")),
+        concat!(
+            r#"<!DOCTYPE html><html lang="fr" dir="ltr">"#,
+            r#"<head prefix="og: https://ogp.me/ns#"><title>Accueil</title></head>"#,
+            r#"<body class="layout home" data-page="index"><main><p>Bonjour</p></main></body>"#,
+            "</html>",
+        )
+    );
+}
+
+#[test]
+fn document_attributes_cannot_be_rendered_without_document() {
+    assert!(view! { HTML [lang="fr"]/ }.try_render_orphan().is_err());
+    assert!(view! { BODY [class="home"]/ }.try_render_orphan().is_err());
+}
+
+fn modal(id: &str) -> View {
+    view! {
+        button [data-open=id] { "Open" }
+        BODY_END {
+            div [id=id, class="modal"]/
+        }
+    }
+}
+
+#[test]
+fn body_end_is_appended_to_body() {
+    let html = view! {
+        BODY_END { script [src="/first.js"] {} }
+        main { @modal["a"] @modal["b"] }
+        footer {}
+    }
+    .render_document(None);
+    assert_eq!(
+        html,
+        concat!(
+            "<!DOCTYPE html><html><head></head><body>",
+            r#"<main><button data-open="a">Open</button><button data-open="b">Open</button></main>"#,
+            "<footer></footer>",
+            r#"<script src="/first.js"></script>"#,
+            r#"<div id="a" class="modal"></div><div id="b" class="modal"></div>"#,
+            "</body></html>",
+        )
+    );
+}
+
+#[test]
+fn body_end_inside_head_is_appended_to_body() {
+    let html = view! {
+        HEAD {
+            title { "Page" }
+            BODY_END { script [src="/analytics.js"] {} }
+        }
+        main {}
+    }
+    .render_document(None);
+    assert_eq!(
+        html,
+        concat!(
+            "<!DOCTYPE html><html><head><title>Page</title></head><body>",
+            r#"<main></main><script src="/analytics.js"></script>"#,
+            "</body></html>",
+        )
+    );
+}
+
+#[test]
+fn body_end_cannot_be_rendered_without_document() {
+    assert!(view! { @modal["a"] }.try_render_orphan().is_err());
+}
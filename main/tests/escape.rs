@@ -0,0 +1,55 @@
+use ibex::prelude::*;
+
+#[test]
+fn text_is_escaped() {
+    let comment = "<img src=x onerror=alert(1)> & more";
+    assert_eq!(
+        view! { p { [comment] } }.render_orphan(),
+        "<p>&lt;img src=x onerror=alert(1)&gt; &amp; more</p>"
+    );
+    assert_eq!(view! { p { "1 < 2" } }.render_orphan(), "<p>1 &lt; 2</p>");
+}
+
+#[test]
+fn pre_escaped_is_not_escaped() {
+    let markup = PreEscaped("<em>trusted</em>");
+    assert_eq!(
+        view! { p { [markup] } }.render_orphan(),
+        "<p><em>trusted</em></p>"
+    );
+}
+
+#[test]
+fn entities_are_not_escaped_twice() {
+    assert_eq!(
+        view! { p { "Tom" &amp "Jerry" &nbsp; &169 } }.render_orphan(),
+        "<p>Tom&amp;Jerry&nbsp;&#169;</p>"
+    );
+}
+
+#[test]
+fn script_cannot_be_closed_by_text() {
+    let data = r#"{"name": "</script><script>alert(1)</script>"}"#;
+    assert_eq!(
+        view! { script [type="application/json"] { [data] } }.render_orphan(),
+        r#"<script type="application/json">{"name": "<\/script>\u003cscript>alert(1)<\/script>"}</script>"#
+    );
+}
+
+#[test]
+fn attribute_value_is_escaped() {
+    let title = r#"The "best" <page> & more"#;
+    assert_eq!(
+        view! { a [href="/", title=title] { "Home" } }.render_orphan(),
+        r#"<a href="/" title="The &quot;best&quot; &lt;page> &amp; more">Home</a>"#
+    );
+}
+
+#[test]
+fn script_cannot_enter_double_escaped_state() {
+    let data = r#"{"note": "<!--<SCRIPT>"}"#;
+    assert_eq!(
+        view! { script [type="application/json"] { [data] } p { "after" } }.render_orphan(),
+        r#"<script type="application/json">{"note": "\u003c!--\u003cSCRIPT>"}</script><p>after</p>"#
+    );
+}
@@ -0,0 +1,36 @@
+use ibex::prelude::*;
+
+#[test]
+fn open_graph_arrays_are_not_deduplicated() {
+    let html = view! {
+        HEAD {
+            meta [property="og:title", content="Layout"]/
+            meta [property="og:image", content="/a.png"]/
+            meta [property="og:image:width", content="400"]/
+            meta [property="og:image", content="/b.png"]/
+            meta [property="og:image:width", content="400"]/
+            meta [property="article:tag", content="rust"]/
+            meta [property="article:tag", content="html"]/
+            meta [property="og:title", content="Page"]/
+        }
+    }
+    .render_document(None);
+
+    let head = html
+        .split_once("<head>")
+        .and_then(|(_, rest)| rest.split_once("</head>"))
+        .map(|(head, _)| head)
+        .unwrap();
+    assert_eq!(
+        head,
+        concat!(
+            r#"<meta property="og:title" content="Page">"#,
+            r#"<meta property="og:image" content="/a.png">"#,
+            r#"<meta property="og:image:width" content="400">"#,
+            r#"<meta property="og:image" content="/b.png">"#,
+            r#"<meta property="og:image:width" content="400">"#,
+            r#"<meta property="article:tag" content="rust">"#,
+            r#"<meta property="article:tag" content="html">"#,
+        )
+    );
+}
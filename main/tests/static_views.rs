@@ -0,0 +1,34 @@
+use ibex::prelude::*;
+use ibex::render::RenderOptions;
+
+/// Static nodes, which do not know where they will be used
+fn snippet() -> View {
+    view! {
+        "fn  main() {\n    run();\n}"
+        em { "  and   more  " }
+    }
+}
+
+#[test]
+fn minified_static_nodes_collapse_whitespace() {
+    assert_eq!(
+        view! { p { [snippet()] } }.render_orphan_with(&RenderOptions::minified()),
+        "<p>fn main() { run(); }<em> and more </em></p>"
+    );
+}
+
+#[test]
+fn minified_static_nodes_keep_whitespace_when_preformatted() {
+    assert_eq!(
+        view! { pre { [snippet()] } }.render_orphan_with(&RenderOptions::minified()),
+        "<pre>fn  main() {\n    run();\n}<em>  and   more  </em></pre>"
+    );
+}
+
+#[test]
+fn static_nodes_are_built_to_indent() {
+    assert_eq!(
+        view! { ul { li { "one" } li { "two" } } }.render_orphan_with(&RenderOptions::pretty(2)),
+        "<ul>\n  <li>one</li>\n  <li>two</li>\n</ul>"
+    );
+}